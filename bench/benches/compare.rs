@@ -0,0 +1,154 @@
+//! Compares tirse with bincode and postcard on the same payloads.
+//!
+//! Run with `cargo bench --manifest-path bench/Cargo.toml`, it prints the size of the
+//! encoding and the throughput of serialization and deserialization for every payload.
+//! The throughput is measured in bytes of the tirse default encoding per second, so the
+//! numbers of different crates are comparable for the same payload.
+
+use serde::{Serialize, Deserialize};
+
+use std::{collections::BTreeMap, fmt, hint::black_box, slice::Iter, time::{Duration, Instant}};
+
+use byteorder::LittleEndian;
+
+use tirse::{
+    WriteWrapper, BinarySerializer, BinaryDeserializer, DefaultBinarySerializer, DefaultBinaryDeserializer,
+    VarintIntDelegate,
+};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Sample {
+    timestamp: u64,
+    x: f64,
+    y: f64,
+    flags: u32,
+    level: i16,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Record {
+    key: String,
+    value: String,
+    comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Index {
+    buckets: BTreeMap<u32, Vec<u16>>,
+    names: Vec<(u8, String)>,
+}
+
+fn numeric() -> Vec<Sample> {
+    (0..10_000)
+        .map(|i| Sample {
+            timestamp: 1_600_000_000_000 + i * 17,
+            x: i as f64 * 0.5,
+            y: -(i as f64) / 3.0,
+            flags: (i % 64) as u32,
+            level: (i % 300) as i16 - 150,
+        })
+        .collect()
+}
+
+fn strings() -> Vec<Record> {
+    (0..5_000)
+        .map(|i| Record {
+            key: format!("user/{}/profile", i),
+            value: "lorem ipsum dolor sit amet ".repeat(1 + i % 4),
+            comment: if i % 3 == 0 { Some(format!("edited {} times", i % 7)) } else { None },
+        })
+        .collect()
+}
+
+fn collections() -> Index {
+    Index {
+        buckets: (0..1_000).map(|i| (i * 7, (0..(i % 32) as u16).collect())).collect(),
+        names: (0..2_000).map(|i| ((i % 256) as u8, format!("n{}", i))).collect(),
+    }
+}
+
+type Fixed = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type FixedReader<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+type Varint = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, VarintIntDelegate, String>;
+type VarintReader<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, VarintIntDelegate, String>;
+
+struct Codec<T> {
+    name: &'static str,
+    to_vec: fn(&T) -> Vec<u8>,
+    from_slice: fn(&[u8]) -> T,
+}
+
+fn codecs<T>() -> Vec<Codec<T>>
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+{
+    vec![
+        Codec {
+            name: "tirse",
+            to_vec: |v| v.serialize(Fixed::new(Vec::new())).unwrap().consume().into_inner(),
+            from_slice: |b| T::deserialize(FixedReader::new(b.iter())).unwrap(),
+        },
+        Codec {
+            name: "tirse varint",
+            to_vec: |v| v.serialize(Varint::new(Vec::new())).unwrap().consume().into_inner(),
+            from_slice: |b| T::deserialize(VarintReader::new(b.iter())).unwrap(),
+        },
+        Codec {
+            name: "bincode",
+            to_vec: |v| bincode::serialize(v).unwrap(),
+            from_slice: |b| bincode::deserialize(b).unwrap(),
+        },
+        Codec {
+            name: "postcard",
+            to_vec: |v| postcard::to_allocvec(v).unwrap(),
+            from_slice: |b| postcard::from_bytes(b).unwrap(),
+        },
+    ]
+}
+
+// runs `f` for about a second, returns the mean time of one run
+fn measure<F>(mut f: F) -> Duration
+where
+    F: FnMut(),
+{
+    let budget = Duration::from_secs(1);
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < budget {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn bench<T>(payload: &str, value: T)
+where
+    T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+{
+    let reference = value.serialize(Fixed::new(Vec::new())).unwrap().consume().into_inner().len() as f64;
+    println!("{}", payload);
+    for codec in codecs::<T>() {
+        let bytes = (codec.to_vec)(&value);
+        assert_eq!((codec.from_slice)(&bytes), value, "{} does not round-trip", codec.name);
+
+        let serialize = measure(|| {
+            black_box((codec.to_vec)(black_box(&value)));
+        });
+        let deserialize = measure(|| {
+            black_box((codec.from_slice)(black_box(&bytes)));
+        });
+        println!(
+            "  {:<14} {:>9} bytes {:>9.1} MB/s serialize {:>9.1} MB/s deserialize",
+            codec.name,
+            bytes.len(),
+            reference / serialize.as_secs_f64() / 1e6,
+            reference / deserialize.as_secs_f64() / 1e6,
+        );
+    }
+}
+
+fn main() {
+    bench("numeric", numeric());
+    bench("strings", strings());
+    bench("collections", collections());
+}
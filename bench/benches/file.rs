@@ -0,0 +1,80 @@
+//! Compares serializing into a raw `File` with serializing through `BufferedWriteWrapper`.
+//!
+//! Run with `cargo bench --manifest-path bench/Cargo.toml --bench file`. The payload has
+//! a thousand fields, `WriteWrapper` passes every one of them to the file in its own
+//! `write_all`, while `BufferedWriteWrapper` writes them in bulk.
+
+use serde::Serialize;
+
+use std::{fs::File, hint::black_box, path::Path, time::{Duration, Instant}};
+
+use tirse::{WriteWrapper, BufferedWriteWrapper, DefaultBinarySerializer};
+
+#[derive(Serialize)]
+pub struct Sample {
+    timestamp: u64,
+    x: f64,
+    y: f64,
+    flags: u32,
+    level: i16,
+}
+
+// 200 samples of 5 fields
+fn payload() -> Vec<Sample> {
+    (0..200)
+        .map(|i| Sample {
+            timestamp: 1_600_000_000_000 + i * 17,
+            x: i as f64 * 0.5,
+            y: -(i as f64) / 3.0,
+            flags: (i % 64) as u32,
+            level: (i % 300) as i16 - 150,
+        })
+        .collect()
+}
+
+type Raw = DefaultBinarySerializer<WriteWrapper<File>, String>;
+type Buffered = DefaultBinarySerializer<BufferedWriteWrapper<File>, String>;
+
+// runs `f` for about a second, returns the mean time of one run
+fn measure<F>(mut f: F) -> Duration
+where
+    F: FnMut(),
+{
+    let budget = Duration::from_secs(1);
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < budget {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn raw(path: &Path, value: &[Sample]) {
+    let file = File::create(path).unwrap();
+    black_box(value.serialize(Raw::new(file)).unwrap());
+}
+
+fn buffered(path: &Path, value: &[Sample]) {
+    let file = File::create(path).unwrap();
+    let s = value.serialize(Buffered::new(file)).unwrap();
+    black_box(s.consume().into_inner().unwrap());
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("tirse-bench-file");
+    let value = payload();
+
+    raw(&path, &value);
+    let expected = std::fs::read(&path).unwrap();
+    buffered(&path, &value);
+    assert_eq!(std::fs::read(&path).unwrap(), expected, "the buffered output differs");
+
+    let raw = measure(|| raw(&path, black_box(&value)));
+    let buffered = measure(|| buffered(&path, black_box(&value)));
+    println!("{} bytes", expected.len());
+    println!("  {:<10} {:>10.1} us", "raw File", raw.as_secs_f64() * 1e6);
+    println!("  {:<10} {:>10.1} us", "buffered", buffered.as_secs_f64() * 1e6);
+
+    std::fs::remove_file(&path).unwrap();
+}
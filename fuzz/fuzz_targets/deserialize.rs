@@ -0,0 +1,47 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use serde::Deserialize;
+use serde_derive::Deserialize;
+
+use std::collections::BTreeMap;
+use std::slice::Iter;
+
+use tirse::DefaultBinaryDeserializer;
+
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub enum Kind {
+    Unit,
+    Newtype(u16),
+    Tuple(u8, i32),
+    Struct { x: i64, y: char },
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct Target<'a> {
+    flag: bool,
+    small: i8,
+    number: u32,
+    float: f64,
+    borrowed: &'a str,
+    name: String,
+    bytes: &'a [u8],
+    kind: Kind,
+    optional: Option<u8>,
+    code: char,
+    pair: (u8, u16),
+    unit: (),
+    tags: Vec<String>,
+    map: BTreeMap<u8, String>,
+}
+
+fuzz_target!(|data: &[u8]| {
+    // must never panic, any malformed input is an error
+    let _ = Target::deserialize(DeserializeFromSlice::new(data.iter()));
+    let _ = Vec::<Kind>::deserialize(DeserializeFromSlice::new(data.iter()));
+});
@@ -0,0 +1,52 @@
+use core::marker;
+use super::io::{
+    BinarySerializerDelegate, BinaryDeserializerDelegate,
+    forward_serializer_delegate, forward_deserializer_delegate,
+};
+
+/// Wraps the delegate `H` and writes the options of a struct as a bitmap before its fields.
+///
+/// The bitmap has a bit for every field, the bit is set if the field is an option that is
+/// present. An absent option takes no space besides its bit, a present one is written
+/// without the tag. Only the options that are fields of a struct itself are affected, not
+/// the ones nested in sequences, tuples or enums. Serialization buffers the fields in
+/// memory, so it requires the `use_std` feature.
+pub struct OptionBitmapDelegate<H> {
+    phantom_data: marker::PhantomData<H>,
+}
+
+impl<H> BinarySerializerDelegate for OptionBitmapDelegate<H>
+where
+    H: BinarySerializerDelegate,
+{
+    forward_serializer_delegate!(
+        H;
+        types,
+        encode_variant, encode_length, encode_sequence_length, encode_char,
+        length_prefixed_variants, encode_unit, primitive_tags, interned_strings,
+        variant_tag, write_integer,
+    );
+
+    fn option_bitmap() -> bool {
+        true
+    }
+}
+
+impl<H> BinaryDeserializerDelegate for OptionBitmapDelegate<H>
+where
+    H: BinaryDeserializerDelegate,
+{
+    forward_deserializer_delegate!(
+        H;
+        types,
+        variant_size, length_size, sequence_length_size, char_size, decode_variant,
+        decode_length, decode_sequence_length, decode_char, length_prefixed_variants,
+        unit_size, decode_unit, primitive_tags, interned_strings, variant_index,
+        read_integer, read_signed_integer, read_variant, read_length, read_sequence_length,
+        read_char,
+    );
+
+    fn option_bitmap() -> bool {
+        true
+    }
+}
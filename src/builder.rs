@@ -0,0 +1,132 @@
+use core::marker::PhantomData;
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
+use super::{
+    io::{Write, BinarySerializerDelegate, DefaultBinarySerializerDelegate},
+    err::{DisplayCollector, DiscardCollector},
+    ser::{BinarySerializer, DEFAULT_MAX_DEPTH},
+    endian::DefaultEndian,
+    varint::VarintSerializerDelegate,
+};
+
+/// Picks the type parameters of a `BinarySerializer` one at a time.
+///
+/// Starts with the default endianness, the default delegate and `DiscardCollector`.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use serde::Serialize;
+/// use tirse::SerializerBuilder;
+///
+/// #[derive(serde_derive::Serialize)]
+/// struct Entry {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let mut bytes = Vec::new();
+/// let serializer = SerializerBuilder::new()
+///     .little_endian()
+///     .varint_lengths()
+///     .collector::<String>()
+///     .build(&mut bytes);
+/// Entry { id: 7, name: "ab".to_owned() }.serialize(serializer).unwrap();
+/// assert_eq!(bytes, [7, 0, 0, 0, 2, b'a', b'b']);
+/// # }
+/// ```
+pub struct SerializerBuilder<E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: DisplayCollector,
+{
+    max_depth: usize,
+    length_prefixed_sequences: bool,
+    phantom_data: PhantomData<(E, H, D)>,
+}
+
+impl SerializerBuilder<DefaultEndian, DefaultBinarySerializerDelegate, DiscardCollector> {
+    pub fn new() -> Self {
+        SerializerBuilder {
+            max_depth: DEFAULT_MAX_DEPTH,
+            length_prefixed_sequences: true,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+impl Default for SerializerBuilder<DefaultEndian, DefaultBinarySerializerDelegate, DiscardCollector> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, H, D> SerializerBuilder<E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: DisplayCollector,
+{
+    fn with<EE, HH, DD>(self) -> SerializerBuilder<EE, HH, DD>
+    where
+        EE: ByteOrder,
+        HH: BinarySerializerDelegate,
+        DD: DisplayCollector,
+    {
+        SerializerBuilder {
+            max_depth: self.max_depth,
+            length_prefixed_sequences: self.length_prefixed_sequences,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn little_endian(self) -> SerializerBuilder<LittleEndian, H, D> {
+        self.with()
+    }
+
+    pub fn big_endian(self) -> SerializerBuilder<BigEndian, H, D> {
+        self.with()
+    }
+
+    pub fn endian<EE>(self) -> SerializerBuilder<EE, H, D>
+    where
+        EE: ByteOrder,
+    {
+        self.with()
+    }
+
+    /// Lengths, sequence lengths and enum tags as varints, see `VarintSerializerDelegate`.
+    pub fn varint_lengths(self) -> SerializerBuilder<E, VarintSerializerDelegate, D> {
+        self.with()
+    }
+
+    pub fn delegate<HH>(self) -> SerializerBuilder<E, HH, D>
+    where
+        HH: BinarySerializerDelegate,
+    {
+        self.with()
+    }
+
+    pub fn collector<DD>(self) -> SerializerBuilder<E, H, DD>
+    where
+        DD: DisplayCollector,
+    {
+        self.with()
+    }
+
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        SerializerBuilder { max_depth, ..self }
+    }
+
+    pub fn length_prefixed_sequences(self, length_prefixed_sequences: bool) -> Self {
+        SerializerBuilder { length_prefixed_sequences, ..self }
+    }
+
+    pub fn build<W>(self, write: W) -> BinarySerializer<W, E, H, D>
+    where
+        W: Write,
+    {
+        BinarySerializer::new(write)
+            .with_max_depth(self.max_depth)
+            .length_prefixed_sequences(self.length_prefixed_sequences)
+    }
+}
@@ -0,0 +1,192 @@
+use core::marker::PhantomData;
+use either::Either;
+use super::{
+    io::{Read, Write, read_small},
+    de::BinaryDeserializerError,
+};
+
+// the longest run of non-zero bytes a block holds
+const BLOCK: usize = 254;
+
+/// Encodes the bytes with Consistent Overhead Byte Stuffing, so the frames written to
+/// a serial link are separated by `0x00` and no zero appears inside a frame.
+///
+/// Call `end_frame` after each message. The bytes of an unfinished frame are held in
+/// the block buffer, at most 254 of them, and are lost by `into_inner`.
+#[derive(Debug, Clone)]
+pub struct CobsWrite<W>
+where
+    W: Write,
+{
+    inner: W,
+    block: [u8; BLOCK],
+    length: usize,
+}
+
+impl<W> CobsWrite<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        CobsWrite {
+            inner,
+            block: [0; BLOCK],
+            length: 0,
+        }
+    }
+
+    /// Writes the last block of the frame and the delimiter.
+    pub fn end_frame(&mut self) -> Result<(), W::Error> {
+        self.emit()?;
+        self.inner.write(&[0])
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    // the code is the distance to the next zero, 0xff means no zero follows the block
+    fn emit(&mut self) -> Result<(), W::Error> {
+        let length = self.length;
+        self.length = 0;
+        self.inner.write(&[length as u8 + 1])?;
+        self.inner.write(&self.block[..length])
+    }
+}
+
+impl<W> Write for CobsWrite<W>
+where
+    W: Write,
+{
+    type Error = W::Error;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        bytes.iter().try_for_each(|&byte| {
+            if byte == 0 {
+                return self.emit();
+            }
+            self.block[self.length] = byte;
+            self.length += 1;
+            if self.length == BLOCK {
+                self.emit()
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes one COBS frame at a time from a continuous stream.
+///
+/// Reading past the delimiter fails with `FrameOverrun`, call `next_frame` to skip
+/// whatever is left of the frame and the delimiter. The bytes are decoded into the
+/// buffer of the deserializer, so nothing is borrowed.
+#[derive(Debug, Clone)]
+pub struct CobsRead<'de, R>
+where
+    R: Read<'de>,
+{
+    inner: R,
+    // the bytes left in the current block
+    remaining: u8,
+    // the block is followed by a zero, unless the frame ends there
+    zero: bool,
+    end: bool,
+    phantom_data: PhantomData<&'de ()>,
+}
+
+impl<'de, R> CobsRead<'de, R>
+where
+    R: Read<'de>,
+{
+    pub fn new(inner: R) -> Self {
+        CobsRead {
+            inner,
+            remaining: 0,
+            zero: false,
+            end: false,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Skips the rest of the current frame and its delimiter.
+    pub fn next_frame(&mut self) -> Result<(), Either<BinaryDeserializerError, R::Error>> {
+        while self.next_byte()?.is_some() {}
+        self.end = false;
+        self.zero = false;
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, Either<BinaryDeserializerError, R::Error>> {
+        loop {
+            if self.end {
+                return Ok(None);
+            }
+            let byte = read_small::<_, [u8; 1], _, _>(&mut self.inner, 1, |b| b[0])?;
+            if self.remaining != 0 {
+                self.remaining -= 1;
+                return match byte {
+                    0 => Err(Either::Left(BinaryDeserializerError::InvalidCobs)),
+                    byte => Ok(Some(byte)),
+                };
+            }
+            if byte == 0 {
+                self.end = true;
+                continue;
+            }
+            let zero = self.zero;
+            self.remaining = byte - 1;
+            self.zero = byte != 0xff;
+            if zero {
+                return Ok(Some(0));
+            }
+        }
+    }
+}
+
+impl<'de, R> Read<'de> for CobsRead<'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Either<BinaryDeserializerError, R::Error>;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let _ = length;
+        None
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        buffer.as_mut()[..length].iter_mut().try_for_each(|b| {
+            self.next_byte()?
+                .map(|byte| *b = byte)
+                .ok_or(Either::Left(BinaryDeserializerError::FrameOverrun))
+        })
+    }
+
+    fn is(&mut self) -> Option<()> {
+        if self.end {
+            None
+        } else {
+            self.inner.is()
+        }
+    }
+}
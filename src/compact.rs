@@ -0,0 +1,84 @@
+use core::convert::TryFrom;
+use byteorder::ByteOrder;
+use serde::{Serialize, Serializer, ser};
+use super::io::{BinarySerializerDelegate, BinaryDeserializerDelegate, DefaultBinaryDeserializerDelegate};
+
+/// An enum or `Option` tag written as `u8`, serialization fails if it does not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CompactVariant(u32);
+
+impl Serialize for CompactVariant {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        u8::try_from(self.0)
+            .map_err(|_| ser::Error::custom("variant index does not fit in u8"))
+            .and_then(|variant| variant.serialize(serializer))
+    }
+}
+
+/// Writes enum and `Option` tags as a single byte instead of a `u32`.
+///
+/// An `Option<u32>` takes 5 bytes instead of 8. Enums with more than 256 variants fail to
+/// serialize. Lengths and chars keep the default encoding.
+pub struct CompactVariantDelegate;
+
+impl BinarySerializerDelegate for CompactVariantDelegate {
+    type Variant = CompactVariant;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        CompactVariant(v)
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        v
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        v
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+impl BinaryDeserializerDelegate for CompactVariantDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        core::mem::size_of::<u8>()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        u32::from(bytes[0])
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+}
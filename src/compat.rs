@@ -0,0 +1,77 @@
+use byteorder::ByteOrder;
+use super::{
+    io::{BinarySerializerDelegate, BinaryDeserializerDelegate},
+    portable::{PortableLength, PortableSerializerDelegate},
+};
+
+/// Matches the wire format of the `speedy` crate.
+///
+/// Lengths of strings, byte arrays, sequences and maps are `u32`, enum tags are `u32`
+/// and chars are `u32` code points, like in `speedy` with its default settings. The byte
+/// order is still chosen by the `E` parameter of the serializer or deserializer and must
+/// match the `Endianness` used with `speedy`.
+///
+/// Not compatible: `speedy` writes the `Option` tag as a single byte while here `Option`
+/// shares the enum tag encoding, and enums with a custom `tag_type` are not supported. A
+/// length above `u32::MAX` fails serialization like with `PortableSerializerDelegate`.
+pub struct SpeedyCompatDelegate;
+
+impl BinarySerializerDelegate for SpeedyCompatDelegate {
+    type Variant = u32;
+    type Length = PortableLength;
+    type SequenceLength = PortableLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        PortableSerializerDelegate::encode_length(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        PortableSerializerDelegate::encode_sequence_length(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+impl BinaryDeserializerDelegate for SpeedyCompatDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn length_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn sequence_length_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn char_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        E::read_u32(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        E::read_u32(bytes) as usize
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        Some(E::read_u32(bytes) as usize)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        let code = E::read_u32(bytes);
+        core::char::from_u32(code).ok_or(code)
+    }
+}
@@ -0,0 +1,161 @@
+use core::marker::PhantomData;
+use byteorder::{ByteOrder, LittleEndian};
+use either::Either;
+use super::{
+    io::{Read, Write, read_small},
+    de::BinaryDeserializerError,
+};
+
+const TABLE: [u32; 256] = table();
+
+// CRC-32 of zlib and ethernet, reflected polynomial 0xedb88320
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+fn update(crc: u32, bytes: &[u8]) -> u32 {
+    !bytes
+        .iter()
+        .fold(!crc, |c, &b| TABLE[((c ^ u32::from(b)) & 0xff) as usize] ^ (c >> 8))
+}
+
+/// Computes the CRC-32 of everything written and appends it on `finalize`.
+///
+/// The checksum is 4 bytes in little endian, like the trailer of gzip.
+#[derive(Debug, Clone)]
+pub struct Crc32Write<W>
+where
+    W: Write,
+{
+    inner: W,
+    crc: u32,
+}
+
+impl<W> Crc32Write<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        Crc32Write { inner, crc: 0 }
+    }
+
+    /// The checksum of the bytes written so far.
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes the checksum and returns the underlying writer.
+    pub fn finalize(mut self) -> Result<W, W::Error> {
+        let mut buffer = [0; 4];
+        LittleEndian::write_u32(&mut buffer, self.crc);
+        self.inner.write(&buffer).map(|()| self.inner)
+    }
+}
+
+impl<W> Write for Crc32Write<W>
+where
+    W: Write,
+{
+    type Error = W::Error;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write(bytes)?;
+        self.crc = update(self.crc, bytes);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Computes the CRC-32 of everything read, `verify` compares it with the trailer
+/// `Crc32Write` appends.
+#[derive(Debug, Clone)]
+pub struct Crc32Read<'de, R>
+where
+    R: Read<'de>,
+{
+    inner: R,
+    crc: u32,
+    phantom_data: PhantomData<&'de ()>,
+}
+
+impl<'de, R> Crc32Read<'de, R>
+where
+    R: Read<'de>,
+{
+    pub fn new(inner: R) -> Self {
+        Crc32Read {
+            inner,
+            crc: 0,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// The checksum of the bytes read so far.
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reads the trailer, call it when the payload is consumed.
+    pub fn verify(mut self) -> Result<R, Either<BinaryDeserializerError, R::Error>> {
+        let computed = self.crc;
+        read_small::<_, [u8; 4], _, _>(&mut self.inner, 4, LittleEndian::read_u32)
+            .and_then(|expected| if expected == computed {
+                Ok(self.inner)
+            } else {
+                Err(Either::Left(BinaryDeserializerError::ChecksumMismatch { expected, computed }))
+            })
+    }
+}
+
+impl<'de, R> Read<'de> for Crc32Read<'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = R::Error;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let crc = &mut self.crc;
+        self.inner.read(length)
+            .map(|x| x.inspect(|bytes| *crc = update(*crc, bytes)))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        self.inner.read_in_buffer(buffer, length)
+            .map(|()| self.crc = update(self.crc, &buffer.as_mut()[..length]))
+    }
+
+    fn is(&mut self) -> Option<()> {
+        self.inner.is()
+    }
+
+    // the trailer is not a part of the payload
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining().map(|remaining| remaining.saturating_sub(4))
+    }
+}
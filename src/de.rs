@@ -1,10 +1,25 @@
-use core::{str, fmt, marker};
-use serde::{de::Visitor, Deserializer};
+use core::{str, fmt, marker, slice, cell::Cell};
+use serde::{de::{Visitor, EnumAccess, VariantAccess, MapAccess, Deserialize, IntoDeserializer}, Deserializer};
 use byteorder::ByteOrder;
 use either::Either;
-use super::{io::{Read, BinaryDeserializerDelegate}, err::{ErrorAdapter, DisplayCollector}};
+use super::{
+    io::{Read, BinaryDeserializerDelegate, IoError, OffsetRead, Take, END_MARKER, read_small, skip},
+    err::{ErrorAdapter, ErrorAt, DisplayCollector},
+    ser::DEFAULT_MAX_DEPTH,
+    endian::{Endian, Endianness},
+    progress::{self, ProgressRead},
+    readable::{HUMAN_READABLE, COMPACT},
+    primitive::tag,
+    interned::ReadTable,
+};
+#[cfg(feature = "use_std")]
+use super::io::CaptureRead;
+#[cfg(feature = "use_std")]
+use super::DefaultBinaryDeserializer;
 
-#[derive(Debug)]
+pub(crate) const DEFAULT_MAX_ALLOC: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum BinaryDeserializerError {
     #[cfg(not(feature = "use_std"))]
     RequiredAlloc,
@@ -15,6 +30,22 @@ pub enum BinaryDeserializerError {
     UnexpectedVariant(u32),
     NotSupported,
     CannotReadBorrowed,
+    InvalidVarint,
+    FrameOverrun,
+    WrongUnit,
+    UnexpectedEof,
+    WrongIntegerWidth(u8),
+    MissingEndMarker,
+    UnexpectedTag(u8),
+    NotSelfDescribing,
+    UnknownString(u32),
+    LengthOverflow(u32),
+    InvalidBool(u8),
+    LengthTooLarge { requested: usize, limit: usize },
+    InvalidCobs,
+    ChecksumMismatch { expected: u32, computed: u32 },
+    TrailingBytes(usize),
+    DepthLimitExceeded,
 }
 
 impl fmt::Display for BinaryDeserializerError {
@@ -23,14 +54,41 @@ impl fmt::Display for BinaryDeserializerError {
 
         match self {
             #[cfg(not(feature = "use_std"))]
-            &RequiredAlloc => write!(f, "required alloc"),
+            RequiredAlloc => write!(f, "required alloc"),
             #[cfg(feature = "use_std")]
-            &FromUtf8Error(ref e) => write!(f, "{}", e),
-            &WrongChar(code) => write!(f, "wrong char code: {}", code),
-            &Utf8Error(ref e) => write!(f, "{}", e),
-            &UnexpectedVariant(code) => write!(f, "unexpected variant code: {}", code),
-            &NotSupported => write!(f, "not supported"),
-            &CannotReadBorrowed => write!(f, "cannot read borrowed"),
+            FromUtf8Error(e) => write!(f, "{}", e),
+            WrongChar(code) => write!(f, "wrong char code: {}", code),
+            Utf8Error(e) => write!(f, "{}", e),
+            UnexpectedVariant(code) => write!(f, "unexpected variant code: {}", code),
+            NotSupported => write!(f, "not supported"),
+            CannotReadBorrowed => write!(f, "cannot read borrowed"),
+            InvalidVarint => write!(f, "invalid varint"),
+            FrameOverrun => write!(f, "read past the end of the frame"),
+            WrongUnit => write!(f, "wrong unit encoding"),
+            UnexpectedEof => write!(f, "unexpected end of input"),
+            WrongIntegerWidth(width) => write!(f, "wrong integer width: {}", width),
+            MissingEndMarker => write!(f, "missing end marker"),
+            UnexpectedTag(tag) => write!(f, "unexpected primitive tag: {}", tag),
+            NotSelfDescribing => write!(f, "the format is not self-describing, the type must drive deserialization"),
+            UnknownString(index) => write!(f, "unknown interned string: {}", index),
+            LengthOverflow(length) => write!(f, "length does not fit in usize: {}", length),
+            InvalidBool(b) => write!(f, "invalid bool: {}", b),
+            LengthTooLarge { requested, limit } => write!(f, "length {} exceeds the limit {}", requested, limit),
+            InvalidCobs => write!(f, "zero byte inside a COBS block"),
+            ChecksumMismatch { expected, computed } => write!(f, "checksum mismatch: expected {:08x}, computed {:08x}", expected, computed),
+            TrailingBytes(count) => write!(f, "{} bytes after the value", count),
+            DepthLimitExceeded => write!(f, "depth limit exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl std::error::Error for BinaryDeserializerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinaryDeserializerError::FromUtf8Error(e) => Some(e),
+            BinaryDeserializerError::Utf8Error(e) => Some(e),
+            _ => None,
         }
     }
 }
@@ -43,9 +101,23 @@ where
     D: DisplayCollector,
 {
     read: R,
+    config: Config,
+    untagged: bool,
+    presence: Option<bool>,
     phantom_data: marker::PhantomData<&'de mut (E, H, D)>,
 }
 
+/// The settings a nested deserializer inherits from its parent.
+#[derive(Clone)]
+struct Config {
+    human_readable: bool,
+    length_prefixed_sequences: bool,
+    max_alloc: usize,
+    depth: usize,
+    max_depth: usize,
+    strings: Option<ReadTable>,
+}
+
 impl<'de, R, E, H, D> BinaryDeserializer<'de, R, E, H, D>
 where
     R: Read<'de>,
@@ -55,39 +127,562 @@ where
 {
     pub fn new(read: R) -> Self {
         BinaryDeserializer {
-            read: read,
+            read,
+            config: Config {
+                human_readable: false,
+                length_prefixed_sequences: true,
+                max_alloc: DEFAULT_MAX_ALLOC,
+                depth: 0,
+                max_depth: DEFAULT_MAX_DEPTH,
+                strings: H::interned_strings().then(ReadTable::default),
+            },
+            untagged: false,
+            presence: None,
             phantom_data: marker::PhantomData,
         }
     }
 
+    /// Whether `deserialize_any` works, with `PrimitiveTagDelegate`.
+    ///
+    /// Only the primitives are tagged, so it decodes a scalar of unknown type and nothing
+    /// else. `#[serde(flatten)]`, untagged enums and values of unknown shape still fail.
+    pub fn is_self_describing(&self) -> bool {
+        H::primitive_tags()
+    }
+
+    /// Whether a sequence or a map starts with its length, true by default, see
+    /// `BinarySerializer::length_prefixed_sequences`.
+    pub fn length_prefixed_sequences(mut self, length_prefixed_sequences: bool) -> Self {
+        self.config.length_prefixed_sequences = length_prefixed_sequences;
+        self
+    }
+
+    /// Limits the length of an owned string or byte buffer, 16 MiB by default.
+    ///
+    /// The length prefix comes from the input, so without the limit a few corrupt bytes
+    /// could make the deserializer allocate gigabytes before reading anything.
+    pub fn with_max_alloc(mut self, max_alloc: usize) -> Self {
+        self.config.max_alloc = max_alloc;
+        self
+    }
+
+    /// Limits the nesting of sequences, maps, structs, options and enum variants with data,
+    /// 128 by default, so a recursive type read from hostile input cannot overflow the stack.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.read
+    }
+
+    /// The reader, to read something the deserializer should not see, like a header.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.read
+    }
+
+    /// Checks that the input is consumed, deserialize from `split` and call it after.
+    ///
+    /// The error counts the bytes left if the reader knows it, otherwise it says one.
+    pub fn finish(mut self) -> Result<(), DeserializerError<'de, R, D>> {
+        match self.read.is() {
+            None => Ok(()),
+            Some(()) => {
+                let count = self.read.remaining().unwrap_or(1);
+                Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::TrailingBytes(count))))
+            },
+        }
+    }
+
     pub fn split(&mut self) -> BinaryDeserializer<'de, &mut R, E, H, D> {
-        BinaryDeserializer::new(&mut self.read)
+        Self::with_read(&mut self.read, &self.config)
+    }
+
+    // a deserializer of another reader with the same settings
+    fn with_read<RR>(read: RR, config: &Config) -> BinaryDeserializer<'de, RR, E, H, D>
+    where
+        RR: Read<'de>,
+    {
+        BinaryDeserializer {
+            read,
+            config: config.clone(),
+            untagged: false,
+            presence: None,
+            phantom_data: marker::PhantomData,
+        }
+    }
+
+    // one more level of nesting, fails past `max_depth`
+    fn enter(self) -> Result<Self, DeserializerError<'de, R, D>> {
+        if self.config.depth >= self.config.max_depth {
+            Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)))
+        } else {
+            let mut s = self;
+            s.config.depth += 1;
+            Ok(s)
+        }
+    }
+
+    fn framed<F, T>(&mut self, f: F) -> Result<T, DeserializerError<'de, R, D>>
+    where
+        F: for<'a, 'b> FnOnce(
+            BinaryDeserializer<'de, &'a mut Take<&'b mut R>, E, H, D>,
+        ) -> Result<T, DeserializerError<'de, Take<&'b mut R>, D>>,
+    {
+        let length = H::read_length::<_, E>(&mut self.read).map_err(ErrorAdapter::Inner)?;
+        let mut take = Take::new(&mut self.read, length);
+        let value = f(Self::with_read(&mut take, &self.config)).map_err(from_frame_error::<&mut R, D>)?;
+        let rest = take.limit();
+        skip::<_, H::SmallBuffer>(&mut self.read, rest)
+            .map_err(Either::Right)
+            .map_err(ErrorAdapter::Inner)
+            .map(|()| value)
+    }
+
+    #[cfg(feature = "use_std")]
+    fn read_string(&mut self) -> Result<String, DeserializerError<'de, R, D>> {
+        self.read_owned().and_then(|bytes| {
+            String::from_utf8(bytes)
+                .map_err(BinaryDeserializerError::FromUtf8Error)
+                .map_err(Either::Left)
+                .map_err(ErrorAdapter::Inner)
+        })
+    }
+
+    // reads a length and that many bytes, the length is checked before allocating
+    #[cfg(feature = "use_std")]
+    fn read_owned(&mut self) -> Result<Vec<u8>, DeserializerError<'de, R, D>> {
+        let limit = self.config.max_alloc;
+        H::read_length::<_, E>(&mut self.read)
+            .and_then(|length| if length > limit {
+                Err(Either::Left(BinaryDeserializerError::LengthTooLarge { requested: length, limit }))
+            } else {
+                Ok(length)
+            })
+            .and_then(|length| {
+                self.read
+                    .read(length)
+                    .map(|x| x.map(ToOwned::to_owned).map_err(Either::Right))
+                    .unwrap_or_else(|| self.read_in_vec(length))
+            })
+            .map_err(ErrorAdapter::Inner)
+    }
+
+    // the fallback of a reader that cannot lend its bytes, the length is already read
+    #[cfg(feature = "use_std")]
+    fn read_in_vec(&mut self, length: usize) -> Result<Vec<u8>, Either<BinaryDeserializerError, R::Error>> {
+        let limit = self.config.max_alloc;
+        if length > limit {
+            return Err(Either::Left(BinaryDeserializerError::LengthTooLarge { requested: length, limit }));
+        }
+        let mut buffer = vec![0; length];
+        self.read.read_in_buffer(&mut buffer, length)
+            .map(move |()| buffer)
+            .map_err(Either::Right)
+    }
+
+    // with `InternedStringDelegate` zero is followed by a new string, which is added to
+    // the table, otherwise it is the index of the string in the table plus one
+    #[cfg(feature = "use_std")]
+    fn interned<V>(mut self, visitor: V) -> Result<V::Value, DeserializerError<'de, R, D>>
+    where
+        V: Visitor<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let strings = self.config.strings.clone().unwrap_or_default();
+        match H::read_variant::<_, E>(&mut self.read).map_err(ErrorAdapter::Inner)? {
+            0 => self.read_string().and_then(|s| {
+                strings.push(&s);
+                visitor.visit_string(s)
+            }),
+            index => strings
+                .get(index - 1)
+                .ok_or(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnknownString(index))))
+                .and_then(|s| visitor.visit_string(s)),
+        }
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn interned<V>(self, visitor: V) -> Result<V::Value, DeserializerError<'de, R, D>>
+    where
+        V: Visitor<'de>,
+    {
+        let _ = visitor;
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::RequiredAlloc)))
+    }
+
+    // with `OptionBitmapDelegate` a struct starts with the bitmap of its present options
+    #[cfg(feature = "use_std")]
+    fn option_bitmap<V>(mut self, len: usize, visitor: V) -> Result<V::Value, DeserializerError<'de, R, D>>
+    where
+        V: Visitor<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let size = len.div_ceil(8);
+        let bitmap = self.read
+            .read(size)
+            .map(|x| x.map(ToOwned::to_owned))
+            .unwrap_or_else(|| {
+                let mut buffer = vec![0; size];
+                self.read.read_in_buffer(&mut buffer, size)
+                    .map(move |()| buffer)
+            })
+            .map_err(Either::Right)
+            .map_err(ErrorAdapter::Inner)?;
+        visitor.visit_seq(OptionBitmapAccess {
+            deserializer: self,
+            bitmap,
+            index: 0,
+            len,
+        })
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn option_bitmap<V>(self, len: usize, visitor: V) -> Result<V::Value, DeserializerError<'de, R, D>>
+    where
+        V: Visitor<'de>,
+    {
+        let _ = len;
+        let _ = visitor;
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::RequiredAlloc)))
+    }
+
+    // with `PrimitiveTagDelegate` reads the tag of a primitive and checks it
+    fn expect_tag(mut self, tag: u8) -> Result<Self, DeserializerError<'de, R, D>> {
+        if !H::primitive_tags() || self.untagged {
+            return Ok(self);
+        }
+        match read_small::<_, H::SmallBuffer, _, _>(&mut self.read, 1, |b| b[0]) {
+            Ok(t) if t == tag => Ok(self),
+            Ok(t) => Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedTag(t)))),
+            Err(e) => Err(ErrorAdapter::Inner(e)),
+        }
+    }
+
+    pub fn byte_order(&self) -> Endianness
+    where
+        E: Endian,
+    {
+        E::ENDIANNESS
+    }
+
+    /// Deserializes `T` and calls `callback` with the number of bytes consumed after every
+    /// element or map entry of the outermost sequence, tuple, struct or map.
+    pub fn deserialize_with_progress<T, F>(self, callback: F) -> Result<T, DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+        F: FnMut(usize),
+    {
+        let position = Cell::new(0);
+        let read = ProgressRead::new(self.read, &position);
+        progress::deserialize_with_progress(Self::with_read(read, &self.config), &position, callback)
+    }
+
+    /// Deserializes `T` and checks that it is followed by `END_MARKER`, the counterpart of
+    /// `BinarySerializer::serialize_with_end_marker`.
+    pub fn deserialize_with_end_marker<T>(mut self) -> Result<T, DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let value = T::deserialize(self.split())?;
+        read_small::<_, [u8; 4], _, _>(&mut self.read, END_MARKER.len(), |bytes| bytes == END_MARKER)
+            .ok()
+            .filter(|&found| found)
+            .map(|_| value)
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::MissingEndMarker)))
+    }
+
+    /// Reads a sequence of `T` without collecting it, returns the number of elements and
+    /// the number of bytes of the whole sequence.
+    ///
+    /// Every element is decoded and dropped, so a `T` that does not allocate, such as an
+    /// integer or a struct of them, is counted without allocating. The sequence must be
+    /// prefixed with its length.
+    pub fn count_elements<T>(mut self) -> Result<(usize, usize), DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let position = Cell::new(0);
+        let read = ProgressRead::new(&mut self.read, &position);
+        let mut deserializer = Self::with_read(read, &self.config);
+        let length = H::read_sequence_length::<_, E>(&mut deserializer.read)
+            .map_err(ErrorAdapter::Inner)?
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSupported)))?;
+        (0..length)
+            .try_for_each(|_| T::deserialize(deserializer.split()).map(|_| ()))
+            .map(|()| (length, position.get()))
+    }
+
+    /// Deserializes `T` and returns the bytes it consumed as well, for example to verify
+    /// a checksum that follows it.
+    #[cfg(feature = "use_std")]
+    pub fn deserialize_captured<T>(&mut self) -> Result<(T, Vec<u8>), DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let mut read = CaptureRead { inner: &mut self.read, captured: Vec::new() };
+        let value = T::deserialize(Self::with_read(&mut read, &self.config))?;
+        Ok((value, read.captured))
+    }
+
+    /// Reads exactly `n` elements that are not prefixed with their count, for formats that
+    /// store the count elsewhere, for example in a header.
+    #[cfg(feature = "use_std")]
+    pub fn read_exact_elements<T>(&mut self, n: usize) -> Result<Vec<T>, DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        (0..n).map(|_| T::deserialize(self.split())).collect()
+    }
+
+    /// Reads a length-prefixed sequence of plain integers or floats, borrowing it from the
+    /// input when possible.
+    ///
+    /// The input is borrowed if the byte order `E` is native and the bytes are aligned
+    /// for `T`, otherwise the elements are copied. The elements must be encoded with
+    /// their fixed width, as the default delegate does, and the reader must support
+    /// borrowed reads.
+    #[cfg(all(feature = "bytemuck", feature = "use_std"))]
+    pub fn read_pod_slice<T>(&mut self) -> Result<std::borrow::Cow<'de, [T]>, DeserializerError<'de, R, D>>
+    where
+        T: bytemuck::Pod + Deserialize<'de>,
+        E: Endian,
+        D: fmt::Display + fmt::Debug,
+    {
+        use std::borrow::Cow;
+
+        let length = H::read_sequence_length::<_, E>(&mut self.read)
+            .map_err(ErrorAdapter::Inner)?
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSupported)))?;
+        let bytes = length
+            .checked_mul(core::mem::size_of::<T>())
+            .ok_or(Either::Left(BinaryDeserializerError::LengthTooLarge {
+                requested: length,
+                limit: usize::MAX / core::mem::size_of::<T>(),
+            }))
+            .and_then(|size| {
+                self.read
+                    .read(size)
+                    .map(|x| x.map_err(Either::Right))
+                    .unwrap_or(Err(Either::Left(BinaryDeserializerError::CannotReadBorrowed)))
+            })
+            .map_err(ErrorAdapter::Inner)?;
+
+        let native = E::ENDIANNESS == if cfg!(target_endian = "little") { Endianness::Little } else { Endianness::Big };
+        match bytemuck::try_cast_slice(bytes) {
+            Ok(slice) if native => Ok(Cow::Borrowed(slice)),
+            _ => {
+                let mut elements = Self::with_read(bytes.iter(), &self.config);
+                (0..length)
+                    .map(|_| T::deserialize(elements.split()).map_err(from_slice_error::<R, D>))
+                    .collect::<Result<_, _>>()
+                    .map(Cow::Owned)
+            },
+        }
+    }
+
+    /// Reads a length prefix and returns a deserializer over that many borrowed bytes.
+    ///
+    /// The nested deserializer cannot read past the frame, and the outer one continues
+    /// after the frame whatever the nested one consumes. The reader must support
+    /// borrowed reads.
+    pub fn read_nested(&mut self) -> Result<NestedDeserializer<'de, E, H, D>, DeserializerError<'de, R, D>> {
+        H::read_length::<_, E>(&mut self.read)
+            .and_then(|length| {
+                self.read
+                    .read(length)
+                    .map(|x| x.map_err(Either::Right))
+                    .unwrap_or(Err(Either::Left(BinaryDeserializerError::CannotReadBorrowed)))
+            })
+            .map_err(ErrorAdapter::Inner)
+            .map(|slice| Self::with_read(slice.iter(), &self.config))
+    }
+}
+
+impl<'de, R, E, H, D> BinaryDeserializer<'de, R, E, H, D>
+where
+    R: Read<'de> + Clone,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector,
+{
+    /// Reads a `T`, or restores the position if it fails, so that another type can be tried.
+    ///
+    /// A derived `#[serde(untagged)]` enum needs `deserialize_any`, so it fails with
+    /// `NotSelfDescribing`, instead try its variants in order, for example
+    /// `d.try_deserialize().map(Shape::Circle).or_else(|_| d.try_deserialize().map(Shape::Square))`.
+    /// The reader is the checkpoint, so it must be cheap to clone, like a slice or a `Cursor`.
+    /// The strings interned by the failed attempt are forgotten as well.
+    pub fn try_deserialize<T>(&mut self) -> Result<T, DeserializerError<'de, R, D>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        let checkpoint = self.read.clone();
+        let strings = self.config.strings.as_ref().map(ReadTable::len);
+        match T::deserialize(self.split()) {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                self.read = checkpoint;
+                if let (Some(table), Some(len)) = (&self.config.strings, strings) {
+                    table.truncate(len);
+                }
+                Err(e)
+            },
+        }
+    }
+}
+
+impl<'de, R, E, H, D> BinaryDeserializer<'de, OffsetRead<R>, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector,
+{
+    /// The number of bytes consumed.
+    pub fn position(&self) -> usize {
+        self.read.position()
+    }
+
+    /// Deserializes `T`, a failure comes with the offset of the bytes that caused it, for
+    /// example the start of an unknown enum tag or of a truncated value.
+    pub fn deserialize_at<T>(&mut self) -> Result<T, ErrorAt<DeserializerError<'de, OffsetRead<R>, D>>>
+    where
+        T: Deserialize<'de>,
+        D: fmt::Display + fmt::Debug,
+    {
+        T::deserialize(self.split()).map_err(|error| ErrorAt { offset: self.read.last_offset(), error })
+    }
+}
+
+/// The error of `from_slice` and `from_slice_prefix`, the error of the reader is the end
+/// of the input.
+#[cfg(feature = "use_std")]
+pub type FromSliceError = ErrorAdapter<Either<BinaryDeserializerError, IoError>, String>;
+
+/// Deserializes `T` from `bytes` with the default encoding, the counterpart of `to_vec`.
+///
+/// Fails with `TrailingBytes` if the value does not take all the bytes.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use tirse::{to_vec, from_slice};
+///
+/// let bytes = to_vec(&(7u16, "seven")).unwrap();
+/// let (number, name) = from_slice::<(u16, &str)>(&bytes).unwrap();
+/// assert_eq!((number, name), (7, "seven"));
+/// # }
+/// ```
+#[cfg(feature = "use_std")]
+pub fn from_slice<'de, T>(bytes: &'de [u8]) -> Result<T, FromSliceError>
+where
+    T: Deserialize<'de>,
+{
+    let mut d = DefaultBinaryDeserializer::<_, String>::new(bytes);
+    let value = T::deserialize(d.split())?;
+    d.finish().map(|()| value)
+}
+
+/// Deserializes `T` from the start of `bytes` and tells how many bytes it took, so the
+/// next value starts at that offset.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use tirse::{to_vec, from_slice_prefix};
+///
+/// let mut bytes = to_vec(&1u8).unwrap();
+/// bytes.extend_from_slice(&to_vec(&"two").unwrap());
+/// let (first, length) = from_slice_prefix::<u8>(&bytes).unwrap();
+/// let (second, _) = from_slice_prefix::<&str>(&bytes[length..]).unwrap();
+/// assert_eq!((first, second), (1, "two"));
+/// # }
+/// ```
+#[cfg(feature = "use_std")]
+pub fn from_slice_prefix<'de, T>(bytes: &'de [u8]) -> Result<(T, usize), FromSliceError>
+where
+    T: Deserialize<'de>,
+{
+    let mut d = DefaultBinaryDeserializer::<_, String>::new(OffsetRead::new(bytes));
+    T::deserialize(d.split()).map(|value| (value, d.position()))
+}
+
+type NestedDeserializer<'de, E, H, D> = BinaryDeserializer<'de, slice::Iter<'de, u8>, E, H, D>;
+
+type DeserializerError<'de, R, D> = ErrorAdapter<Either<BinaryDeserializerError, <R as Read<'de>>::Error>, D>;
+
+fn from_frame_error<'de, R, D>(e: DeserializerError<'de, Take<R>, D>) -> DeserializerError<'de, R, D>
+where
+    R: Read<'de>,
+    D: DisplayCollector,
+{
+    match e {
+        ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(Either::Left(e)),
+        ErrorAdapter::Inner(Either::Right(Either::Left(IoError { .. }))) => {
+            ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::FrameOverrun))
+        },
+        ErrorAdapter::Inner(Either::Right(Either::Right(e))) => ErrorAdapter::Inner(Either::Right(e)),
+        ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+    }
+}
+
+#[cfg(all(feature = "bytemuck", feature = "use_std"))]
+fn from_slice_error<'de, R, D>(e: DeserializerError<'de, slice::Iter<'de, u8>, D>) -> DeserializerError<'de, R, D>
+where
+    R: Read<'de>,
+    D: DisplayCollector,
+{
+    match e {
+        ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(Either::Left(e)),
+        ErrorAdapter::Inner(Either::Right(IoError { .. })) => {
+            ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedEof))
+        },
+        ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
     }
 }
 
 macro_rules! primitive {
-    ($ty:ty, $method:ident, $visitor_method:ident, $reader:expr) => {
-        fn $method<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    ($ty:ty, $tag:expr, $method:ident, $visitor_method:ident, $reader:expr) => {
+        primitive!($ty, $tag, $method, $visitor_method, $reader, H::SmallBuffer);
+    };
+    ($ty:ty, $tag:expr, $method:ident, $visitor_method:ident, $reader:expr, $buffer:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: Visitor<'de>,
         {
             use core::mem;
 
-            self.read.read(mem::size_of::<$ty>())
-                .map(|x| x.map($reader))
-                .unwrap_or_else(|| {
-                    let mut buffer = <H::SmallBuffer as Default>::default();
-                    self.read.read_in_buffer(&mut buffer, mem::size_of::<$ty>())
-                        .map(move |()| $reader(buffer.as_ref()))
-                })
-                .map_err(Either::Right)
+            let mut s = self.expect_tag($tag)?;
+            read_small::<_, $buffer, _, _>(&mut s.read, mem::size_of::<$ty>(), $reader)
                 .map_err(ErrorAdapter::Inner)
                 .and_then(|x| visitor.$visitor_method(x))
         }
     }
 }
 
-impl<'a, 'de, R, E, H, D> Deserializer<'de> for BinaryDeserializer<'de, R, E, H, D>
+macro_rules! integer {
+    ($ty:ty, $tag:expr, $method:ident, $visitor_method:ident, $reader:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            use core::mem;
+
+            let mut s = self.expect_tag($tag)?;
+            H::$reader::<_, E>(&mut s.read, mem::size_of::<$ty>())
+                .map_err(ErrorAdapter::Inner)
+                .and_then(|x| visitor.$visitor_method(x as $ty))
+        }
+    }
+}
+
+impl<'de, R, E, H, D> Deserializer<'de> for BinaryDeserializer<'de, R, E, H, D>
 where
     R: Read<'de>,
     E: ByteOrder + 'de,
@@ -96,75 +691,112 @@ where
 {
     type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    // the format is not self-describing, so `deserialize_any`, `deserialize_identifier` and
+    // `deserialize_ignored_any` fail, custom `Deserialize` code must call the typed methods,
+    // only with `PrimitiveTagDelegate` `deserialize_any` decodes a scalar by its tag
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let _ = visitor;
-        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSupported)))
+        if !H::primitive_tags() {
+            return Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing)));
+        }
+
+        let t = read_small::<_, H::SmallBuffer, _, _>(&mut self.read, 1, |b| b[0])
+            .map_err(ErrorAdapter::Inner)?;
+        let s = BinaryDeserializer { untagged: true, ..self };
+        match t {
+            tag::BOOL => s.deserialize_bool(visitor),
+            tag::I8 => s.deserialize_i8(visitor),
+            tag::I16 => s.deserialize_i16(visitor),
+            tag::I32 => s.deserialize_i32(visitor),
+            tag::I64 => s.deserialize_i64(visitor),
+            tag::I128 => s.deserialize_i128(visitor),
+            tag::U8 => s.deserialize_u8(visitor),
+            tag::U16 => s.deserialize_u16(visitor),
+            tag::U32 => s.deserialize_u32(visitor),
+            tag::U64 => s.deserialize_u64(visitor),
+            tag::U128 => s.deserialize_u128(visitor),
+            tag::F32 => s.deserialize_f32(visitor),
+            tag::F64 => s.deserialize_f64(visitor),
+            tag::CHAR => s.deserialize_char(visitor),
+            tag::STR => s.deserialize_str(visitor),
+            tag::BYTES => s.deserialize_bytes(visitor),
+            t => Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedTag(t)))),
+        }
     }
 
-    primitive!(bool, deserialize_bool, visit_bool, |b: &[u8]| b[0] != 0);
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut s = self.expect_tag(tag::BOOL)?;
+        read_small::<_, H::SmallBuffer, _, _>(&mut s.read, 1, |b| b[0])
+            .and_then(|b| match b {
+                0 => Ok(false),
+                1 => Ok(true),
+                b => Err(Either::Left(BinaryDeserializerError::InvalidBool(b))),
+            })
+            .map_err(ErrorAdapter::Inner)
+            .and_then(|x| visitor.visit_bool(x))
+    }
 
-    primitive!(i8, deserialize_i8, visit_i8, |b: &[u8]| b[0] as i8);
-    primitive!(i16, deserialize_i16, visit_i16, E::read_i16);
-    primitive!(i32, deserialize_i32, visit_i32, E::read_i32);
-    primitive!(i64, deserialize_i64, visit_i64, E::read_i64);
+    primitive!(i8, tag::I8, deserialize_i8, visit_i8, |b: &[u8]| b[0] as i8);
+    integer!(i16, tag::I16, deserialize_i16, visit_i16, read_signed_integer);
+    integer!(i32, tag::I32, deserialize_i32, visit_i32, read_signed_integer);
+    integer!(i64, tag::I64, deserialize_i64, visit_i64, read_signed_integer);
+    // the small buffer of the delegate may not fit 128-bit integers
+    primitive!(i128, tag::I128, deserialize_i128, visit_i128, E::read_i128, [u8; 16]);
 
-    primitive!(u8, deserialize_u8, visit_u8, |b: &[u8]| b[0]);
-    primitive!(u16, deserialize_u16, visit_u16, E::read_u16);
-    primitive!(u32, deserialize_u32, visit_u32, E::read_u32);
-    primitive!(u64, deserialize_u64, visit_u64, E::read_u64);
+    primitive!(u8, tag::U8, deserialize_u8, visit_u8, |b: &[u8]| b[0]);
+    integer!(u16, tag::U16, deserialize_u16, visit_u16, read_integer);
+    integer!(u32, tag::U32, deserialize_u32, visit_u32, read_integer);
+    integer!(u64, tag::U64, deserialize_u64, visit_u64, read_integer);
+    primitive!(u128, tag::U128, deserialize_u128, visit_u128, E::read_u128, [u8; 16]);
 
-    primitive!(f32, deserialize_f32, visit_f32, E::read_f32);
-    primitive!(f64, deserialize_f64, visit_f64, E::read_f64);
+    primitive!(f32, tag::F32, deserialize_f32, visit_f32, E::read_f32);
+    primitive!(f64, tag::F64, deserialize_f64, visit_f64, E::read_f64);
 
-    fn deserialize_char<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::char_size())
-            .map(|x| x.map(H::decode_char::<E>))
-            .unwrap_or_else(|| {
-                let mut buffer = <H::SmallBuffer as Default>::default();
-                self.read.read_in_buffer(&mut buffer, H::char_size())
-                    .map(move |()| H::decode_char::<E>(&buffer.as_ref()))
-            })
-            .map_err(Either::Right)
-            .and_then(|v| v
-                .map_err(BinaryDeserializerError::WrongChar)
-                .map_err(Either::Left)
-            )
+        let mut s = self.expect_tag(tag::CHAR)?;
+        H::read_char::<_, E>(&mut s.read)
             .map_err(ErrorAdapter::Inner)
             .and_then(|v| visitor.visit_char(v))
     }
 
-    fn deserialize_str<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::length_size())
-            .map(|x| {
-                x
-                    .map(H::decode_length::<E>)
-                    .map_err(Either::Right)
-            })
-            .unwrap_or(Err(Either::Left(BinaryDeserializerError::CannotReadBorrowed)))
-            .map_err(ErrorAdapter::Inner)
-            .and_then(|length| {
-                self.read
-                    .read(length)
-                    .unwrap()
-                    .map_err(Either::Right)
-                    .and_then(|slice| str::from_utf8(slice)
-                        .map_err(BinaryDeserializerError::Utf8Error)
-                        .map_err(Either::Left)
-                    )
-                    .map_err(ErrorAdapter::Inner)
-                    .and_then(|s| visitor.visit_borrowed_str(s))
-            })
+        let mut s = self.expect_tag(tag::STR)?;
+        if s.config.strings.is_some() {
+            return s.interned(visitor);
+        }
+        let length = H::read_length::<_, E>(&mut s.read).map_err(ErrorAdapter::Inner)?;
+        match s.read.read(length) {
+            Some(slice) => slice
+                .map_err(Either::Right)
+                .and_then(|slice| str::from_utf8(slice)
+                    .map_err(BinaryDeserializerError::Utf8Error)
+                    .map_err(Either::Left)
+                )
+                .map_err(ErrorAdapter::Inner)
+                .and_then(|s| visitor.visit_borrowed_str(s)),
+            // the reader cannot lend, the visitor gets a temporary
+            #[cfg(feature = "use_std")]
+            None => s.read_in_vec(length)
+                .and_then(|bytes| String::from_utf8(bytes)
+                    .map_err(BinaryDeserializerError::FromUtf8Error)
+                    .map_err(Either::Left)
+                )
+                .map_err(ErrorAdapter::Inner)
+                .and_then(|s| visitor.visit_str(&s)),
+            #[cfg(not(feature = "use_std"))]
+            None => Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::CannotReadBorrowed))),
+        }
     }
 
     #[cfg(not(feature = "use_std"))]
@@ -177,60 +809,29 @@ where
     }
 
     #[cfg(feature = "use_std")]
-    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::length_size())
-            .map(|x| x.map(H::decode_length::<E>))
-            .unwrap_or_else(|| {
-                let mut buffer = <H::SmallBuffer as Default>::default();
-                self.read.read_in_buffer(&mut buffer, H::length_size())
-                    .map(move |()| H::decode_length::<E>(&buffer.as_ref()[..H::length_size()]))
-            })
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .and_then(|length| {
-                self.read
-                    .read(length)
-                    .map(|x| x.map(ToOwned::to_owned))
-                    .unwrap_or_else(|| {
-                        let mut buffer = Vec::new();
-                        buffer.resize(length, 0);
-                        self.read.read_in_buffer(&mut buffer, length)
-                            .map(move |()| buffer)
-                    })
-                    .map_err(Either::Right)
-                    .map_err(ErrorAdapter::Inner)
-                    .and_then(|bytes| {
-                        String::from_utf8(bytes)
-                            .map_err(BinaryDeserializerError::FromUtf8Error)
-                            .map_err(Either::Left)
-                            .map_err(ErrorAdapter::Inner)
-                            .and_then(|s| visitor.visit_string(s))
-                    })
-            })
+        let mut s = self.expect_tag(tag::STR)?;
+        if s.config.strings.is_some() {
+            return s.interned(visitor);
+        }
+        s.read_string().and_then(|s| visitor.visit_string(s))
     }
 
-    fn deserialize_bytes<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::length_size())
-            .map(|x| {
-                x
-                    .map(H::decode_length::<E>)
-                    .map_err(Either::Right)
-            })
-            .unwrap_or(Err(Either::Left(BinaryDeserializerError::CannotReadBorrowed)))
+        let mut s = self.expect_tag(tag::BYTES)?;
+        H::read_length::<_, E>(&mut s.read)
             .map_err(ErrorAdapter::Inner)
             .and_then(|length| {
-                self.read
+                s.read
                     .read(length)
-                    .unwrap()
-                    .map_err(Either::Right)
+                    .map(|x| x.map_err(Either::Right))
+                    .unwrap_or(Err(Either::Left(BinaryDeserializerError::CannotReadBorrowed)))
                     .map_err(ErrorAdapter::Inner)
                     .and_then(|slice| visitor.visit_borrowed_bytes(slice))
             })
@@ -246,62 +847,48 @@ where
     }
 
     #[cfg(feature = "use_std")]
-    fn deserialize_byte_buf<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::length_size())
-            .map(|x| x.map(H::decode_length::<E>))
-            .unwrap_or_else(|| {
-                let mut buffer = <H::SmallBuffer as Default>::default();
-                self.read.read_in_buffer(&mut buffer, H::length_size())
-                    .map(move |()| H::decode_length::<E>(buffer.as_ref()))
-            })
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .and_then(|length| {
-                self.read
-                    .read(length)
-                    .map(|x| x.map(ToOwned::to_owned))
-                    .unwrap_or_else(|| {
-                        let mut buffer = Vec::new();
-                        buffer.resize(length, 0);
-                        self.read.read_in_buffer(&mut buffer, length)
-                            .map(move |()| buffer)
-                    })
-                    .map_err(Either::Right)
-                    .map_err(ErrorAdapter::Inner)
-                    .and_then(|x| visitor.visit_byte_buf(x))
-            })
+        let mut s = self.expect_tag(tag::BYTES)?;
+        s.read_owned().and_then(|x| visitor.visit_byte_buf(x))
     }
 
     fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.read
-            .read(H::variant_size())
-            .map(|x| x.map(H::decode_variant::<E>))
-            .unwrap_or_else(|| {
-                let mut buffer = <H::SmallBuffer as Default>::default();
-                self.read.read_in_buffer(&mut buffer, H::variant_size())
-                    .map(move |()| H::decode_variant::<E>(buffer.as_ref()))
-            })
-            .map_err(Either::Right)
+        match self.presence {
+            Some(true) => return visitor.visit_some(BinaryDeserializer { presence: None, ..self.enter()? }),
+            Some(false) => return visitor.visit_none(),
+            None => (),
+        }
+        H::read_variant::<_, E>(&mut self.read)
             .map_err(ErrorAdapter::Inner)
             .and_then(|variant| match variant {
                 0 => visitor.visit_none(),
-                1 => visitor.visit_some(self),
-                t @ _ => Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(t)))),
+                1 => self.enter().and_then(|s| visitor.visit_some(s)),
+                t => Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(t)))),
             })
     }
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_unit()
+        if H::unit_size() == 0 {
+            return visitor.visit_unit();
+        }
+
+        read_small::<_, H::SmallBuffer, _, _>(&mut self.read, H::unit_size(), H::decode_unit)
+            .and_then(|valid| if valid {
+                Ok(())
+            } else {
+                Err(Either::Left(BinaryDeserializerError::WrongUnit))
+            })
+            .map_err(ErrorAdapter::Inner)
+            .and_then(|()| visitor.visit_unit())
     }
 
     fn deserialize_unit_struct<V>(
@@ -313,7 +900,7 @@ where
         V: Visitor<'de>,
     {
         let _ = name;
-        visitor.visit_unit()
+        self.deserialize_unit(visitor)
     }
 
     fn deserialize_newtype_struct<V>(
@@ -324,22 +911,38 @@ where
     where
         V: Visitor<'de>,
     {
-        let _ = name;
-        visitor.visit_newtype_struct(self)
+        let human_readable = match name {
+            HUMAN_READABLE => true,
+            COMPACT => false,
+            _ => self.config.human_readable,
+        };
+        let mut s = self;
+        s.config.human_readable = human_readable;
+        visitor.visit_newtype_struct(BinaryDeserializer { presence: None, ..s })
     }
 
+    // the length is read before visiting, so the visitor gets it from `size_hint`
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SequenceAccess::new(self))
+        let mut s = self.enter()?;
+        if !s.config.length_prefixed_sequences {
+            return visitor.visit_seq(SequenceAccess::new_with_length(s, usize::MAX));
+        }
+        H::read_sequence_length::<_, E>(&mut s.read)
+            .map_err(ErrorAdapter::Inner)
+            .and_then(|length| {
+                let length = length.unwrap_or(usize::MAX);
+                visitor.visit_seq(SequenceAccess::new_with_length(s, length))
+            })
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SequenceAccess::new_with_length(self, len))
+        self.enter().and_then(|s| visitor.visit_seq(SequenceAccess::new_with_length(s, len)))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -356,37 +959,21 @@ where
         self.deserialize_tuple(len, visitor)
     }
 
+    // the count of entries is a sequence length, as the length of a sequence
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        use serde::de::MapAccess;
-
-        impl<'de, R, E, H, D> MapAccess<'de> for SequenceAccess<'de, R, E, H, D>
-        where
-            R: Read<'de>,
-            E: ByteOrder + 'de,
-            H: BinaryDeserializerDelegate,
-            D: DisplayCollector + fmt::Display + fmt::Debug,
-        {
-            type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
-
-            fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
-            where
-                K: DeserializeSeed<'de>,
-            {
-                self.next_element_seed(seed)
-            }
-
-            fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
-            where
-                V: DeserializeSeed<'de>,
-            {
-                self.next_element_seed(seed).map(Option::unwrap)
-            }
+        let mut s = self.enter()?;
+        if !s.config.length_prefixed_sequences {
+            return visitor.visit_map(SequenceAccess::new_with_length(s, usize::MAX));
         }
-
-        visitor.visit_map(SequenceAccess::new(self))
+        H::read_sequence_length::<_, E>(&mut s.read)
+            .map_err(ErrorAdapter::Inner)
+            .and_then(|length| {
+                let length = length.unwrap_or(usize::MAX);
+                visitor.visit_map(SequenceAccess::new_with_length(s, length))
+            })
     }
 
     fn deserialize_struct<V>(
@@ -398,8 +985,10 @@ where
     where
         V: Visitor<'de>,
     {
-        let _ = self;
         let _ = name;
+        if H::option_bitmap() {
+            return self.enter()?.option_bitmap(fields.len(), visitor);
+        }
         self.deserialize_tuple(fields.len(), visitor)
     }
 
@@ -412,71 +1001,11 @@ where
     where
         V: Visitor<'de>,
     {
-        use serde::de::{EnumAccess, VariantAccess, Deserialize, IntoDeserializer};
-
-        impl<'de, R, E, H, D> EnumAccess<'de> for BinaryDeserializer<'de, R, E, H, D>
-        where
-            R: Read<'de>,
-            E: ByteOrder + 'de,
-            H: BinaryDeserializerDelegate,
-            D: DisplayCollector + fmt::Display + fmt::Debug,
-        {
-            type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
-            type Variant = Self;
-
-            fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
-            where
-                V: DeserializeSeed<'de>,
-            {
-                u32::deserialize(self.split())
-                    .map(IntoDeserializer::into_deserializer)
-                    .and_then(|variant| seed.deserialize(variant))
-                    .map(|value| (value, self))
-            }
-        }
-
-        impl<'de, R, E, H, D> VariantAccess<'de> for BinaryDeserializer<'de, R, E, H, D>
-        where
-            R: Read<'de>,
-            E: ByteOrder + 'de,
-            H: BinaryDeserializerDelegate,
-            D: DisplayCollector + fmt::Display + fmt::Debug,
-        {
-            type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
-
-            fn unit_variant(self) -> Result<(), Self::Error> {
-                Ok(())
-            }
-
-            fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
-            where
-                T: DeserializeSeed<'de>,
-            {
-                seed.deserialize(self)
-            }
-
-            fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
-            where
-                V: Visitor<'de>,
-            {
-                self.deserialize_tuple(len, visitor)
-            }
-
-            fn struct_variant<V>(
-                self,
-                fields: &'static [&'static str],
-                visitor: V,
-            ) -> Result<V::Value, Self::Error>
-            where
-                V: Visitor<'de>,
-            {
-                self.deserialize_tuple(fields.len(), visitor)
-            }
-        }
-
         let _ = name;
-        let _ = variants;
-        visitor.visit_enum(self)
+        visitor.visit_enum(CheckedEnumAccess {
+            deserializer: BinaryDeserializer { presence: None, ..self },
+            variants,
+        })
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -485,7 +1014,7 @@ where
     {
         let _ = self;
         let _ = visitor;
-        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSupported)))
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing)))
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -494,17 +1023,159 @@ where
     {
         let _ = self;
         let _ = visitor;
-        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSupported)))
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing)))
     }
 
     fn is_human_readable(&self) -> bool {
-        false
+        self.config.human_readable
+    }
+}
+
+impl<'de, R, E, H, D> EnumAccess<'de> for BinaryDeserializer<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        H::read_variant::<_, E>(&mut self.read)
+            .map_err(ErrorAdapter::Inner)
+            .map(IntoDeserializer::into_deserializer)
+            .and_then(|variant| seed.deserialize(variant))
+            .map(|value| (value, self))
+    }
+}
+
+// serde limits variant indices to `u32`, an index the enum rejects and that is not
+// covered by `variants` is reported as `UnexpectedVariant`, an enum with
+// `#[serde(other)]` still accepts it
+struct CheckedEnumAccess<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    deserializer: BinaryDeserializer<'de, R, E, H, D>,
+    variants: &'static [&'static str],
+}
+
+impl<'de, R, E, H, D> EnumAccess<'de> for CheckedEnumAccess<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
+    type Variant = BinaryDeserializer<'de, R, E, H, D>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let CheckedEnumAccess { mut deserializer, variants } = self;
+        let tag = H::read_variant::<_, E>(&mut deserializer.read).map_err(ErrorAdapter::Inner)?;
+        // an unknown tag is passed on as an index past the end, for `#[serde(other)]`
+        let variant = H::variant_index(tag, variants).unwrap_or(variants.len() as u32);
+        seed.deserialize(variant.into_deserializer())
+            .map_err(|e| if variant as usize >= variants.len() {
+                ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(tag)))
+            } else {
+                e
+            })
+            .map(|value| (value, deserializer))
+    }
+}
+
+impl<'de, R, E, H, D> VariantAccess<'de> for BinaryDeserializer<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
+
+    fn unit_variant(mut self) -> Result<(), Self::Error> {
+        if H::length_prefixed_variants() {
+            self.framed(|_| Ok(()))
+        } else {
+            Ok(())
+        }
+    }
+
+    // only a payload is a level of nesting, the serializer counts it the same way
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut s = self.enter()?;
+        if H::length_prefixed_variants() {
+            s.framed(|d| seed.deserialize(d))
+        } else {
+            seed.deserialize(s)
+        }
+    }
+
+    fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if H::length_prefixed_variants() {
+            self.framed(|d| d.deserialize_tuple(len, visitor))
+        } else {
+            self.deserialize_tuple(len, visitor)
+        }
+    }
+
+    fn struct_variant<V>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if H::length_prefixed_variants() {
+            self.framed(|d| d.deserialize_tuple(fields.len(), visitor))
+        } else {
+            self.deserialize_tuple(fields.len(), visitor)
+        }
     }
 }
 
 use serde::de::{SeqAccess, DeserializeSeed};
 
-struct SequenceAccess<'de, R, E, H, D>
+/// Gives the elements of a sequence, or the entries of a map, to a `Visitor`.
+///
+/// Without a length the elements are read until the sequence length stored by the delegate
+/// is exhausted or the input ends.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use serde::de::SeqAccess;
+/// use tirse::{DefaultBinaryDeserializer, SequenceAccess};
+///
+/// let bytes = [1, 2, 3];
+/// let deserializer = DefaultBinaryDeserializer::<_, String>::new(bytes.iter());
+/// let mut access = SequenceAccess::new_with_length(deserializer, 3);
+///
+/// let mut sum = 0;
+/// while let Some(x) = access.next_element::<u8>().unwrap() {
+///     sum += x;
+/// }
+/// assert_eq!(sum, 6);
+/// # }
+/// ```
+pub struct SequenceAccess<'de, R, E, H, D>
 where
     R: Read<'de>,
     E: ByteOrder,
@@ -522,19 +1193,23 @@ where
     H: BinaryDeserializerDelegate,
     D: DisplayCollector + fmt::Display + fmt::Debug,
 {
-    fn new(d: BinaryDeserializer<'de, R, E, H, D>) -> Self {
+    pub fn new(d: BinaryDeserializer<'de, R, E, H, D>) -> Self {
         SequenceAccess {
             deserializer: d,
             len: None,
         }
     }
 
-    fn new_with_length(d: BinaryDeserializer<'de, R, E, H, D>, length: usize) -> Self {
+    pub fn new_with_length(d: BinaryDeserializer<'de, R, E, H, D>, length: usize) -> Self {
         SequenceAccess {
             deserializer: d,
             len: Some(length),
         }
     }
+
+    pub fn into_inner(self) -> BinaryDeserializer<'de, R, E, H, D> {
+        self.deserializer
+    }
 }
 
 impl<'de, R, E, H, D> SeqAccess<'de> for SequenceAccess<'de, R, E, H, D>
@@ -550,32 +1225,103 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        let d = self.deserializer.split();
+        let mut d = self.deserializer.split();
 
         let length = match self.len {
             Some(length) => length,
             None => {
-                d.read
-                    .read(H::sequence_length_size())
-                    .map(|x| x.map(H::decode_sequence_length::<E>))
-                    .unwrap_or_else(|| {
-                        let mut buffer = <H::SmallBuffer as Default>::default();
-                        d.read.read_in_buffer(&mut buffer, H::sequence_length_size())
-                            .map(move |()| H::decode_sequence_length::<E>(buffer.as_ref()))
-                    })
-                    .map_err(Either::Right)
+                H::read_sequence_length::<_, E>(&mut d.read)
                     .map_err(ErrorAdapter::Inner)?
-                    .unwrap_or(usize::max_value())
+                    .unwrap_or(usize::MAX)
             }
         };
 
-        if length > 0 {
-            self.len = Some(length - 1);
+        if length == usize::MAX {
+            // a sequence without a length stays unbounded and ends with the input
+            self.len = Some(length);
             d.read.is()
                 .map(|()| seed.deserialize(d).map(Some))
                 .unwrap_or(Ok(None))
+        } else if length > 0 {
+            // a truncated input fails rather than yields a shorter sequence
+            self.len = Some(length - 1);
+            seed.deserialize(d).map(Some)
         } else {
             Ok(None)
         }
     }
+
+    // `usize::MAX` stands for a sequence that runs until the input ends
+    fn size_hint(&self) -> Option<usize> {
+        self.len.filter(|&len| len != usize::MAX)
+    }
+}
+
+// the fields of a struct with `OptionBitmapDelegate`, an option field takes its presence
+// from the bitmap instead of the tag
+#[cfg(feature = "use_std")]
+struct OptionBitmapAccess<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    deserializer: BinaryDeserializer<'de, R, E, H, D>,
+    bitmap: Vec<u8>,
+    index: usize,
+    len: usize,
+}
+
+#[cfg(feature = "use_std")]
+impl<'de, R, E, H, D> SeqAccess<'de> for OptionBitmapAccess<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.index == self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        let presence = Some(self.bitmap[index / 8] & (1 << (index % 8)) != 0);
+        seed.deserialize(BinaryDeserializer { presence, ..self.deserializer.split() }).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+impl<'de, R, E, H, D> MapAccess<'de> for SequenceAccess<'de, R, E, H, D>
+where
+    R: Read<'de>,
+    E: ByteOrder + 'de,
+    H: BinaryDeserializerDelegate,
+    D: DisplayCollector + fmt::Display + fmt::Debug,
+{
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, R::Error>, D>;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.next_element_seed(seed)
+    }
+
+    // the length counts entries, so only the key takes one from it
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.deserializer.split())
+    }
 }
@@ -0,0 +1,48 @@
+use byteorder::{ByteOrder, LittleEndian, BigEndian};
+
+#[cfg(feature = "default_le")]
+type Selected = LittleEndian;
+
+#[cfg(not(feature = "default_le"))]
+type Selected = fallback::Selected;
+
+#[cfg(not(feature = "default_le"))]
+mod fallback {
+    #[cfg(feature = "default_be")]
+    pub type Selected = byteorder::BigEndian;
+
+    #[cfg(not(feature = "default_be"))]
+    pub type Selected = byteorder::NativeEndian;
+}
+
+/// The byte order of `DefaultBinarySerializer` and `DefaultBinaryDeserializer`.
+///
+/// It is `LittleEndian` with the feature `default_le`, `BigEndian` with `default_be`,
+/// and `NativeEndian` otherwise. With both features `default_le` takes precedence.
+pub type DefaultEndian = Selected;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for byteorder::LittleEndian {}
+    impl Sealed for byteorder::BigEndian {}
+}
+
+/// The byte orders whose endianness is known at runtime.
+pub trait Endian: ByteOrder + private::Sealed {
+    const ENDIANNESS: Endianness;
+}
+
+impl Endian for LittleEndian {
+    const ENDIANNESS: Endianness = Endianness::Little;
+}
+
+impl Endian for BigEndian {
+    const ENDIANNESS: Endianness = Endianness::Big;
+}
@@ -1,5 +1,8 @@
-use serde::{ser, de};
+use serde::ser;
+#[cfg(not(feature = "use_std"))]
+use serde::de;
 use core::fmt;
+use either::Either;
 
 pub trait DisplayCollector {
     fn display<T>(msg: &T) -> Self
@@ -7,7 +10,52 @@ pub trait DisplayCollector {
         T: ?Sized + fmt::Display;
 }
 
-#[derive(Debug)]
+/// Drops the message, for targets without an allocator where nothing could store it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiscardCollector;
+
+impl DisplayCollector for DiscardCollector {
+    fn display<T>(msg: &T) -> Self
+    where
+        T: ?Sized + fmt::Display,
+    {
+        let _ = msg;
+        DiscardCollector
+    }
+}
+
+impl fmt::Display for DiscardCollector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error message discarded")
+    }
+}
+
+impl ser::Serialize for DiscardCollector {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_unit_struct("DiscardCollector")
+    }
+}
+
+/// An error together with the offset in the input where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorAt<E> {
+    pub offset: usize,
+    pub error: E,
+}
+
+impl<E> fmt::Display for ErrorAt<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum ErrorAdapter<E, D>
 where
     D: DisplayCollector,
@@ -16,6 +64,7 @@ where
     Outer(D),
 }
 
+#[cfg(not(feature = "use_std"))]
 impl<E, D> ser::Error for ErrorAdapter<E, D>
 where
     D: DisplayCollector + fmt::Display + fmt::Debug,
@@ -31,6 +80,7 @@ where
     }
 }
 
+#[cfg(not(feature = "use_std"))]
 impl<E, D> de::Error for ErrorAdapter<E, D>
 where
     D: DisplayCollector + fmt::Display + fmt::Debug,
@@ -55,22 +105,93 @@ where
         use self::ErrorAdapter::*;
 
         match self {
-            &Inner(ref e) => write!(f, "{}", e),
-            &Outer(ref d) => write!(f, "{}", d),
+            Inner(e) => write!(f, "{}", e),
+            Outer(d) => write!(f, "{}", d),
+        }
+    }
+}
+
+impl<L, R, D> ErrorAdapter<Either<L, R>, D>
+where
+    D: DisplayCollector,
+{
+    /// Wraps an error of the reader or the writer, for code around the serializer that
+    /// does its own I/O with the same error type.
+    pub fn from_io(e: R) -> Self {
+        ErrorAdapter::Inner(Either::Right(e))
+    }
+
+    /// The error of the reader or the writer, if that is what failed.
+    pub fn io_error(&self) -> Option<&R> {
+        match self {
+            ErrorAdapter::Inner(Either::Right(e)) => Some(e),
+            _ => None,
         }
     }
 }
 
 #[cfg(feature = "use_std")]
 mod std {
-    use std::{fmt, error, string};
-    use super::{ErrorAdapter, DisplayCollector};
+    use std::{fmt, error, string, io};
+    use serde::{ser, de};
+    use either::Either;
+    use super::{ErrorAdapter, ErrorAt, DisplayCollector};
+
+    impl<E> error::Error for ErrorAt<E>
+    where
+        E: error::Error,
+    {}
+
+    impl<L, D> From<io::Error> for ErrorAdapter<Either<L, io::Error>, D>
+    where
+        D: DisplayCollector,
+    {
+        fn from(e: io::Error) -> Self {
+            ErrorAdapter::from_io(e)
+        }
+    }
 
+    // the error of the format or of the reader or writer is the source, a message of the
+    // `Serialize` or `Deserialize` code has none
     impl<E, D> error::Error for ErrorAdapter<E, D>
     where
         D: DisplayCollector + fmt::Display + fmt::Debug,
-        E: fmt::Display + fmt::Debug,
-    {}
+        E: error::Error + 'static,
+    {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            match self {
+                ErrorAdapter::Inner(e) => Some(e),
+                ErrorAdapter::Outer(_) => None,
+            }
+        }
+    }
+
+    // serde requires `std::error::Error` of its errors with `std`
+    impl<E, D> ser::Error for ErrorAdapter<E, D>
+    where
+        D: DisplayCollector + fmt::Display + fmt::Debug,
+        E: error::Error + 'static,
+    {
+        fn custom<T>(msg: T) -> Self
+        where
+            T: fmt::Display,
+        {
+            ErrorAdapter::Outer(D::display(&msg))
+        }
+    }
+
+    impl<E, D> de::Error for ErrorAdapter<E, D>
+    where
+        D: DisplayCollector + fmt::Display + fmt::Debug,
+        E: error::Error + 'static,
+    {
+        fn custom<T>(msg: T) -> Self
+        where
+            T: fmt::Display,
+        {
+            ErrorAdapter::Outer(D::display(&msg))
+        }
+    }
 
     impl DisplayCollector for string::String {
         fn display<T>(msg: &T) -> Self
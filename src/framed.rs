@@ -0,0 +1,176 @@
+use core::{convert::TryFrom, marker::PhantomData};
+use byteorder::{ByteOrder, LittleEndian};
+use either::Either;
+use super::{
+    io::{Read, read_small, skip},
+    de::BinaryDeserializerError,
+};
+#[cfg(feature = "use_std")]
+use super::{io::Write, ser::BinarySerializerError};
+
+/// Prefixes each message with its length, a `u32` in little endian.
+///
+/// The length is known only when the message is complete, so the payload is buffered
+/// in memory rather than patched in place, which would require a seekable writer.
+/// `finalize` writes the prefix and the payload and clears the buffer for the next
+/// message.
+#[cfg(feature = "use_std")]
+#[derive(Debug, Clone)]
+pub struct FramedWrite<W>
+where
+    W: Write,
+{
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "use_std")]
+impl<W> FramedWrite<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        FramedWrite {
+            inner,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Writes the message buffered so far as one frame.
+    pub fn finalize(&mut self) -> Result<(), Either<BinarySerializerError, W::Error>> {
+        let length = u32::try_from(self.buffer.len())
+            .map_err(|_| Either::Left(BinarySerializerError::SizeLimitExceeded))?;
+        let mut prefix = [0; 4];
+        LittleEndian::write_u32(&mut prefix, length);
+        self.inner.write(&prefix)
+            .and_then(|()| self.inner.write(&self.buffer))
+            .map_err(Either::Right)
+            .map(|()| self.buffer.clear())
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The underlying writer, a message that is not finalized is dropped.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<W> Write for FramedWrite<W>
+where
+    W: Write,
+{
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.buffer.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Reads the messages `FramedWrite` writes, one frame at a time.
+///
+/// The prefix is read before the first byte of a frame, reading past the end of the
+/// frame fails with `FrameOverrun`. Call `next_frame` to skip what is left of the
+/// current frame.
+#[derive(Debug, Clone)]
+pub struct FramedRead<'de, R>
+where
+    R: Read<'de>,
+{
+    inner: R,
+    // the bytes left in the current frame, unknown until the prefix is read
+    limit: Option<usize>,
+    phantom_data: PhantomData<&'de ()>,
+}
+
+impl<'de, R> FramedRead<'de, R>
+where
+    R: Read<'de>,
+{
+    pub fn new(inner: R) -> Self {
+        FramedRead {
+            inner,
+            limit: None,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Skips the rest of the current frame, the next read starts a new one.
+    pub fn next_frame(&mut self) -> Result<(), Either<BinaryDeserializerError, R::Error>> {
+        let rest = self.limit()?;
+        skip::<_, [u8; 16]>(&mut self.inner, rest)
+            .map_err(Either::Right)
+            .map(|()| self.limit = None)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn limit(&mut self) -> Result<usize, Either<BinaryDeserializerError, R::Error>> {
+        match self.limit {
+            Some(limit) => Ok(limit),
+            None => {
+                let length = read_small::<_, [u8; 4], _, _>(&mut self.inner, 4, LittleEndian::read_u32)?;
+                let limit = usize::try_from(length)
+                    .map_err(|_| Either::Left(BinaryDeserializerError::LengthOverflow(length)))?;
+                self.limit = Some(limit);
+                Ok(limit)
+            },
+        }
+    }
+
+    fn check(&mut self, length: usize) -> Result<usize, Either<BinaryDeserializerError, R::Error>> {
+        self.limit().and_then(|limit| if limit < length {
+            Err(Either::Left(BinaryDeserializerError::FrameOverrun))
+        } else {
+            Ok(limit - length)
+        })
+    }
+}
+
+impl<'de, R> Read<'de> for FramedRead<'de, R>
+where
+    R: Read<'de>,
+{
+    type Error = Either<BinaryDeserializerError, R::Error>;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let rest = match self.check(length) {
+            Ok(rest) => rest,
+            Err(e) => return Some(Err(e)),
+        };
+        let limit = &mut self.limit;
+        self.inner.read(length)
+            .map(|x| x.map_err(Either::Right).inspect(|_| *limit = Some(rest)))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        let rest = self.check(length)?;
+        self.inner.read_in_buffer(buffer, length)
+            .map_err(Either::Right)
+            .map(|()| self.limit = Some(rest))
+    }
+
+    fn is(&mut self) -> Option<()> {
+        match self.limit {
+            Some(0) => None,
+            _ => self.inner.is(),
+        }
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.limit
+    }
+}
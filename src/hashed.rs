@@ -0,0 +1,98 @@
+use core::marker;
+use byteorder::ByteOrder;
+use either::Either;
+use super::{
+    io::{
+        Read, BinarySerializerDelegate, BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate, forward_serializer_delegate,
+        forward_deserializer_delegate,
+    },
+    de::BinaryDeserializerError,
+};
+
+/// Wraps the delegate `H` and writes a hash of the variant name instead of its index.
+///
+/// The hash is the 32-bit FNV-1a of the name, so variants can be reordered or inserted
+/// without breaking stored data. Renaming a variant breaks it instead. Reading looks up
+/// the hash among the names of the variants, two names with equal hashes are ambiguous
+/// and the first one wins. The variants, the tags of options included, are written as a
+/// `u32` integer of `H` whatever variant `H` writes, so a narrower variant like the one of
+/// `CompactVariantDelegate` does not cut the hash.
+pub struct NameHashDelegate<H> {
+    phantom_data: marker::PhantomData<H>,
+}
+
+/// The 32-bit FNV-1a hash of `name`, the tag of the variant `name` in `NameHashDelegate`.
+pub const fn name_hash(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut hash = 0x811c_9dc5u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    hash
+}
+
+impl<H> BinarySerializerDelegate for NameHashDelegate<H>
+where
+    H: BinarySerializerDelegate,
+{
+    type Variant = u32;
+    type Length = H::Length;
+    type SequenceLength = H::SequenceLength;
+    type Char = H::Char;
+
+    forward_serializer_delegate!(
+        H;
+        encode_length, encode_sequence_length, encode_char, length_prefixed_variants,
+        encode_unit, primitive_tags, interned_strings, option_bitmap, write_integer,
+    );
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn variant_tag(index: u32, name: &'static str) -> u32 {
+        let _ = index;
+        name_hash(name)
+    }
+}
+
+impl<H> BinaryDeserializerDelegate for NameHashDelegate<H>
+where
+    H: BinaryDeserializerDelegate,
+{
+    forward_deserializer_delegate!(
+        H;
+        types,
+        length_size, sequence_length_size, char_size, decode_length, decode_sequence_length,
+        decode_char, length_prefixed_variants, unit_size, decode_unit, primitive_tags,
+        interned_strings, option_bitmap, read_integer, read_signed_integer, read_length,
+        read_sequence_length, read_char,
+    );
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::variant_size()).map(|x| x as u32)
+    }
+
+    fn variant_index(tag: u32, variants: &'static [&'static str]) -> Option<u32> {
+        variants
+            .iter()
+            .position(|name| name_hash(name) == tag)
+            .map(|index| index as u32)
+    }
+}
@@ -0,0 +1,60 @@
+//! Encodes an `Instant` as the `Duration` elapsed since a reference instant.
+//!
+//! Use with `#[serde(with = "tirse::instant_as_duration")]` and run the serialization and
+//! the deserialization inside `with_reference`. `Instant` is opaque and only meaningful
+//! within one process, so the reference must be the same on both sides, a value decoded
+//! with another reference or in another process is meaningless. Instants earlier than the
+//! reference are an error.
+
+use std::{cell::Cell, time::{Duration, Instant}};
+use serde::{Serialize, Serializer, Deserialize, Deserializer, ser, de};
+
+thread_local! {
+    static REFERENCE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+struct Restore(Option<Instant>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        REFERENCE.with(|r| r.set(self.0))
+    }
+}
+
+/// Runs `f` with `reference` as the reference instant of the current thread.
+pub fn with_reference<F, T>(reference: Instant, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let _restore = Restore(REFERENCE.with(|r| r.replace(Some(reference))));
+    f()
+}
+
+fn reference() -> Option<Instant> {
+    REFERENCE.with(Cell::get)
+}
+
+pub fn serialize<S>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    reference()
+        .ok_or_else(|| ser::Error::custom("no reference instant"))
+        .and_then(|reference| instant
+            .checked_duration_since(reference)
+            .ok_or_else(|| ser::Error::custom("instant is earlier than the reference"))
+        )
+        .and_then(|duration| duration.serialize(serializer))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Instant, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let reference = reference().ok_or_else(|| de::Error::custom("no reference instant"))?;
+    Duration::deserialize(deserializer)
+        .and_then(|duration| reference
+            .checked_add(duration)
+            .ok_or_else(|| de::Error::custom("instant is out of range"))
+        )
+}
@@ -0,0 +1,123 @@
+use core::marker;
+#[cfg(feature = "use_std")]
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+use super::io::{
+    BinarySerializerDelegate, BinaryDeserializerDelegate,
+    forward_serializer_delegate, forward_deserializer_delegate,
+};
+
+/// Wraps the delegate `H` and writes every string only once.
+///
+/// The first occurrence of a string is written in full and added to a table, the later
+/// ones are written as the index in the table, which the deserializer rebuilds while
+/// reading. It pays off for many repeated strings, for example keys or column names, and
+/// costs one variant per string otherwise. A string decoded from the table cannot be
+/// borrowed from the input. The table requires the `use_std` feature.
+pub struct InternedStringDelegate<H> {
+    phantom_data: marker::PhantomData<H>,
+}
+
+impl<H> BinarySerializerDelegate for InternedStringDelegate<H>
+where
+    H: BinarySerializerDelegate,
+{
+    forward_serializer_delegate!(
+        H;
+        types,
+        encode_variant, encode_length, encode_sequence_length, encode_char,
+        length_prefixed_variants, encode_unit, primitive_tags, option_bitmap, variant_tag,
+        write_integer,
+    );
+
+    fn interned_strings() -> bool {
+        true
+    }
+}
+
+impl<H> BinaryDeserializerDelegate for InternedStringDelegate<H>
+where
+    H: BinaryDeserializerDelegate,
+{
+    forward_deserializer_delegate!(
+        H;
+        types,
+        variant_size, length_size, sequence_length_size, char_size, decode_variant,
+        decode_length, decode_sequence_length, decode_char, length_prefixed_variants,
+        unit_size, decode_unit, primitive_tags, option_bitmap, variant_index, read_integer,
+        read_signed_integer, read_variant, read_length, read_sequence_length, read_char,
+    );
+
+    fn interned_strings() -> bool {
+        true
+    }
+}
+
+/// Strings written so far and their indices, shared by the nested serializers.
+#[cfg(feature = "use_std")]
+#[derive(Clone, Default)]
+pub(crate) struct WriteTable(Rc<RefCell<HashMap<String, u32>>>);
+
+#[cfg(feature = "use_std")]
+impl WriteTable {
+    /// Returns the index of `s` if it was written before, otherwise remembers it.
+    pub fn intern(&self, s: &str) -> Option<u32> {
+        let mut table = self.0.borrow_mut();
+        match table.get(s) {
+            Some(&index) => Some(index),
+            None => {
+                let index = table.len() as u32;
+                table.insert(s.to_owned(), index);
+                None
+            },
+        }
+    }
+}
+
+/// Strings read so far, shared by the nested deserializers.
+#[cfg(feature = "use_std")]
+#[derive(Clone, Default)]
+pub(crate) struct ReadTable(Rc<RefCell<Vec<String>>>);
+
+#[cfg(feature = "use_std")]
+impl ReadTable {
+    pub fn push(&self, s: &str) {
+        self.0.borrow_mut().push(s.to_owned())
+    }
+
+    pub fn get(&self, index: u32) -> Option<String> {
+        self.0.borrow().get(index as usize).cloned()
+    }
+
+    /// The number of strings, a checkpoint to `truncate` back to.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Forgets the strings read after the checkpoint `len`.
+    pub fn truncate(&self, len: usize) {
+        self.0.borrow_mut().truncate(len)
+    }
+}
+
+#[cfg(not(feature = "use_std"))]
+#[derive(Clone, Default)]
+pub(crate) struct WriteTable;
+
+#[cfg(not(feature = "use_std"))]
+#[derive(Clone, Default)]
+pub(crate) struct ReadTable;
+
+#[cfg(not(feature = "use_std"))]
+impl ReadTable {
+    pub fn len(&self) -> usize {
+        0
+    }
+
+    pub fn truncate(&self, len: usize) {
+        let _ = len;
+    }
+}
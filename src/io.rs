@@ -1,8 +1,17 @@
 use core::{slice, fmt, ops};
 use byteorder::ByteOrder;
+use either::Either;
 use serde::ser;
+use super::de::BinaryDeserializerError;
+
+/// The sentinel written after a value by `BinarySerializer::serialize_with_end_marker`.
+pub const END_MARKER: [u8; 4] = [0xe0, 0x0f, 0x5e, 0x0d];
 
 pub trait Read<'de> {
+    /// With `use_std` an `Error`, the `source` of the error of the deserializer holds it.
+    #[cfg(feature = "use_std")]
+    type Error: std::error::Error + 'static;
+    #[cfg(not(feature = "use_std"))]
     type Error: fmt::Display + fmt::Debug;
 
     fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>>;
@@ -11,32 +20,45 @@ pub trait Read<'de> {
     where
         B: AsMut<[u8]>;
 
-    fn is(&self) -> Option<()>;
+    /// `Some` if at least one more byte is available, `None` at the end of the input.
+    ///
+    /// A sequence without a length runs until it is `None`. A reader that fails to tell
+    /// says `Some`, so the next read reports the failure.
+    fn is(&mut self) -> Option<()>;
+
+    /// The number of bytes left, if the reader knows it.
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
 }
 
-impl<'a, 'de, R> Read<'de> for &'a mut R
+impl<'de, R> Read<'de> for &mut R
 where
     R: Read<'de>,
 {
     type Error = R::Error;
 
     fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
-        (&mut **self).read(length)
+        (**self).read(length)
     }
 
     fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
     where
         B: AsMut<[u8]>,
     {
-        (&mut **self).read_in_buffer(buffer, length)
+        (**self).read_in_buffer(buffer, length)
     }
 
-    fn is(&self) -> Option<()> {
-        (&**self).is()
+    fn is(&mut self) -> Option<()> {
+        (**self).is()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        (**self).remaining()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IoError {
     missing: ops::Range<usize>,
 }
@@ -47,36 +69,82 @@ impl fmt::Display for IoError {
     }
 }
 
+#[cfg(feature = "use_std")]
+impl std::error::Error for IoError {}
+
+fn sub_slice(slice: &[u8], position: usize, length: usize) -> Result<&[u8], IoError> {
+    let rest = slice.get(position..).unwrap_or(&[]);
+    rest.get(..length)
+        .ok_or(IoError { missing: rest.len()..length })
+}
+
+fn sub_slice_mut(slice: &mut [u8], position: usize, length: usize) -> Result<&mut [u8], IoError> {
+    let rest = slice.get_mut(position..).unwrap_or(&mut []);
+    let limit = rest.len();
+    rest.get_mut(..length)
+        .ok_or(IoError { missing: limit..length })
+}
+
 impl<'de> Read<'de> for slice::Iter<'de, u8> {
     type Error = IoError;
 
     fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
-        let limit = self.as_slice().len();
-        if limit < length {
-            Some(Err(IoError { missing: limit..length }))
+        let slice = self.as_slice();
+        Some(sub_slice(slice, 0, length).inspect(|_| *self = slice[length..].iter()))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        let slice = self.as_slice();
+        sub_slice_mut(buffer.as_mut(), 0, length)
+            .and_then(|target| sub_slice(slice, 0, length).map(|s| target.copy_from_slice(s)))
+            .map(|()| *self = slice[length..].iter())
+    }
+
+    fn is(&mut self) -> Option<()> {
+        if !self.as_slice().is_empty() {
+            Some(())
         } else {
-            let s = &self.as_slice()[0..length];
-            self.nth(length - 1);
-            Some(Ok(s))
+            None
         }
     }
 
+    fn remaining(&self) -> Option<usize> {
+        Some(self.as_slice().len())
+    }
+}
+
+impl<'de> Read<'de> for &'de [u8] {
+    type Error = IoError;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let slice = *self;
+        Some(sub_slice(slice, 0, length).inspect(|_| *self = &slice[length..]))
+    }
+
     fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
     where
         B: AsMut<[u8]>,
     {
-        self.read(length)
-            .unwrap()
-            .map(|x| buffer.as_mut()[0..length].copy_from_slice(x))
+        let slice = *self;
+        sub_slice_mut(buffer.as_mut(), 0, length)
+            .and_then(|target| sub_slice(slice, 0, length).map(|s| target.copy_from_slice(s)))
+            .map(|()| *self = &slice[length..])
     }
 
-    fn is(&self) -> Option<()> {
-        if self.as_slice().len() != 0 {
+    fn is(&mut self) -> Option<()> {
+        if !self.is_empty() {
             Some(())
         } else {
             None
         }
     }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
 }
 
 pub trait BinaryDeserializerDelegate {
@@ -88,15 +156,211 @@ pub trait BinaryDeserializerDelegate {
     fn char_size() -> usize;
 
     fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder;
+    /// The length of a string or a byte array.
     fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder;
+    /// The count of elements of a sequence or of entries of a map, `None` if it is not
+    /// known and the sequence runs to the end of the input.
     fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder;
     fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder;
+
+    fn length_prefixed_variants() -> bool {
+        false
+    }
+
+    /// The size of `()` and of a unit struct.
+    fn unit_size() -> usize {
+        0
+    }
+
+    /// Whether the bytes of `()` or of a unit struct are valid.
+    fn decode_unit(bytes: &[u8]) -> bool {
+        let _ = bytes;
+        true
+    }
+
+    /// Whether every primitive starts with its tag, see `PrimitiveTagDelegate`.
+    fn primitive_tags() -> bool {
+        false
+    }
+
+    /// Whether repeated strings refer to their first occurrence, see `InternedStringDelegate`.
+    fn interned_strings() -> bool {
+        false
+    }
+
+    /// Whether structs start with the bitmap of their present options, see `OptionBitmapDelegate`.
+    fn option_bitmap() -> bool {
+        false
+    }
+
+    /// Maps the tag read from the input to the index of the variant in `variants`.
+    fn variant_index(tag: u32, variants: &'static [&'static str]) -> Option<u32> {
+        let _ = variants;
+        Some(tag)
+    }
+
+    fn read_integer<'de, R, E>(read: &mut R, size: usize) -> Result<u64, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, Self::SmallBuffer, _, _>(read, size, |b| E::read_uint(b, size))
+    }
+
+    fn read_signed_integer<'de, R, E>(read: &mut R, size: usize) -> Result<i64, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, size).map(|x| sign_extend(x, size))
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, Self::SmallBuffer, _, _>(read, Self::variant_size(), Self::decode_variant::<E>)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, Self::SmallBuffer, _, _>(read, Self::length_size(), Self::decode_length::<E>)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, Self::SmallBuffer, _, _>(read, Self::sequence_length_size(), Self::decode_sequence_length::<E>)
+    }
+
+    fn read_char<'de, R, E>(read: &mut R) -> Result<char, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, Self::SmallBuffer, _, _>(read, Self::char_size(), Self::decode_char::<E>)
+            .and_then(|v| v
+                .map_err(BinaryDeserializerError::WrongChar)
+                .map_err(Either::Left)
+            )
+    }
+}
+
+pub(crate) fn sign_extend(v: u64, size: usize) -> i64 {
+    let shift = 64 - 8 * size.clamp(1, 8);
+    ((v << shift) as i64) >> shift
+}
+
+pub(crate) fn read_small<'de, R, B, T, F>(read: &mut R, length: usize, f: F) -> Result<T, Either<BinaryDeserializerError, R::Error>>
+where
+    R: Read<'de>,
+    B: AsRef<[u8]> + AsMut<[u8]> + Default,
+    F: Fn(&[u8]) -> T,
+{
+    let decode = |bytes: Option<&[u8]>| match bytes {
+        Some(bytes) if bytes.len() == length => Ok(f(bytes)),
+        _ => Err(Either::Left(BinaryDeserializerError::UnexpectedEof)),
+    };
+
+    match read.read(length) {
+        Some(x) => x.map_err(Either::Right).and_then(|x| decode(Some(x))),
+        None => {
+            let mut buffer = B::default();
+            if buffer.as_ref().len() < length {
+                return decode(None);
+            }
+            read.read_in_buffer(&mut buffer, length)
+                .map_err(Either::Right)
+                .and_then(|()| decode(buffer.as_ref().get(..length)))
+        },
+    }
+}
+
+pub(crate) fn skip<'de, R, B>(read: &mut R, length: usize) -> Result<(), R::Error>
+where
+    R: Read<'de>,
+    B: AsRef<[u8]> + AsMut<[u8]> + Default,
+{
+    let mut rest = length;
+    while rest > 0 {
+        let mut buffer = B::default();
+        let chunk = rest.min(buffer.as_ref().len());
+        read.read(chunk)
+            .map(|x| x.map(|_| ()))
+            .unwrap_or_else(|| read.read_in_buffer(&mut buffer, chunk))?;
+        rest -= chunk;
+    }
+    Ok(())
+}
+
+pub(crate) struct Take<R> {
+    inner: R,
+    limit: usize,
+}
+
+impl<R> Take<R> {
+    pub fn new(inner: R, limit: usize) -> Self {
+        Take { inner, limit }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl<'de, R> Read<'de> for Take<R>
+where
+    R: Read<'de>,
+{
+    type Error = Either<IoError, R::Error>;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        if self.limit < length {
+            Some(Err(Either::Left(IoError { missing: self.limit..length })))
+        } else {
+            let limit = &mut self.limit;
+            self.inner.read(length).map(|x| {
+                x.map_err(Either::Right).inspect(|_| *limit -= length)
+            })
+        }
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        if self.limit < length {
+            Err(Either::Left(IoError { missing: self.limit..length }))
+        } else {
+            self.inner.read_in_buffer(buffer, length)
+                .map_err(Either::Right)
+                .map(|()| self.limit -= length)
+        }
+    }
+
+    fn is(&mut self) -> Option<()> {
+        if self.limit != 0 {
+            self.inner.is()
+        } else {
+            None
+        }
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining().map(|remaining| remaining.min(self.limit))
+    }
 }
 
 pub struct DefaultBinaryDeserializerDelegate;
 
 impl BinaryDeserializerDelegate for DefaultBinaryDeserializerDelegate {
-    type SmallBuffer = [u8; 8];
+    type SmallBuffer = [u8; 16];
 
     fn variant_size() -> usize {
         core::mem::size_of::<u32>()
@@ -107,7 +371,7 @@ impl BinaryDeserializerDelegate for DefaultBinaryDeserializerDelegate {
     }
 
     fn sequence_length_size() -> usize {
-        0
+        core::mem::size_of::<usize>()
     }
 
     fn char_size() -> usize {
@@ -127,8 +391,7 @@ impl BinaryDeserializerDelegate for DefaultBinaryDeserializerDelegate {
     }
 
     fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
-        let _ = bytes;
-        None
+        Some(Self::decode_length::<E>(bytes))
     }
 
     fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
@@ -138,9 +401,33 @@ impl BinaryDeserializerDelegate for DefaultBinaryDeserializerDelegate {
 }
 
 pub trait Write {
+    /// With `use_std` an `Error`, the `source` of the error of the serializer holds it.
+    #[cfg(feature = "use_std")]
+    type Error: std::error::Error + 'static;
+    #[cfg(not(feature = "use_std"))]
     type Error: fmt::Display + fmt::Debug;
 
     fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Passes the bytes a buffering writer holds back to the underlying sink.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<W> Write for &mut W
+where
+    W: Write,
+{
+    type Error = W::Error;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        (**self).write(bytes)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
 }
 
 impl<'de> Write for slice::IterMut<'de, u8> {
@@ -154,142 +441,1439 @@ impl<'de> Write for slice::IterMut<'de, u8> {
         if limit < length {
             Err(IoError { missing: limit..length })
         } else {
-            let mut temp = (&mut []).iter_mut();
+            let mut temp = [].iter_mut();
             mem::swap(&mut temp, self);
             let slice = temp.into_slice();
-            slice.copy_from_slice(bytes);
+            slice[..length].copy_from_slice(bytes);
             *self = slice[length..].iter_mut();
             Ok(())
         }
     }
 }
 
-pub trait BinarySerializerDelegate {
-    type Variant: ser::Serialize;
-    type Length: ser::Serialize;
-    type SequenceLength: ser::Serialize;
-    type Char: ser::Serialize;
+/// Fixed-capacity circular buffer, the oldest bytes are overwritten when it is full.
+///
+/// A single write longer than the capacity fails and leaves the buffer untouched. After
+/// a wrap-around the buffer may start in the middle of a value, so the reader must be able
+/// to resynchronize, for example by using fixed-size records.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    raw: B,
+    position: usize,
+    length: usize,
+}
 
-    fn encode_variant(v: u32) -> Self::Variant;
-    fn encode_length(v: usize) -> Self::Length;
-    fn encode_sequence_length(v: usize) -> Self::SequenceLength;
-    fn encode_char(v: char) -> Self::Char;
+impl<B> From<B> for RingBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn from(v: B) -> Self {
+        RingBuffer {
+            raw: v,
+            position: 0,
+            length: 0,
+        }
+    }
 }
 
-pub struct DefaultBinarySerializerDelegate;
+impl<B> RingBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    pub fn capacity(&self) -> usize {
+        self.raw.as_ref().len()
+    }
 
-impl BinarySerializerDelegate for DefaultBinarySerializerDelegate {
-    type Variant = u32;
-    type Length = usize;
-    type SequenceLength = usize;
-    type Char = u32;
+    pub fn len(&self) -> usize {
+        self.length
+    }
 
-    fn encode_variant(v: u32) -> Self::Variant {
-        v
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
     }
 
-    fn encode_length(v: usize) -> Self::Length {
-        v
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let raw = self.raw.as_ref();
+        if self.length < raw.len() {
+            (&raw[..self.length], &[])
+        } else {
+            let (newest, oldest) = raw.split_at(self.position);
+            (oldest, newest)
+        }
     }
 
-    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
-        v
+    pub fn into_inner(self) -> B {
+        self.raw
     }
+}
 
-    fn encode_char(v: char) -> Self::Char {
-        v as _
+impl<B> Write for RingBuffer<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Error = IoError;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let capacity = self.capacity();
+        let length = bytes.len();
+        if capacity < length {
+            Err(IoError { missing: capacity..length })
+        } else {
+            let tail = capacity - self.position;
+            let raw = self.raw.as_mut();
+            if length <= tail {
+                raw[self.position..(self.position + length)].copy_from_slice(bytes);
+            } else {
+                raw[self.position..].copy_from_slice(&bytes[..tail]);
+                raw[..(length - tail)].copy_from_slice(&bytes[tail..]);
+            }
+            self.position = (self.position + length) % capacity.max(1);
+            self.length = (self.length + length).min(capacity);
+            Ok(())
+        }
     }
 }
 
-#[cfg(feature = "use_std")]
-pub use self::with_std::{WriteWrapper, ReadWrapper};
+/// Stack buffer of exactly `N` bytes, a write past the end fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FixedBuffer<const N: usize> {
+    raw: [u8; N],
+    length: usize,
+}
 
-#[cfg(feature = "use_std")]
-mod with_std {
-    use super::{Write, Read};
-    use std::io;
+impl<const N: usize> FixedBuffer<N> {
+    pub fn new() -> Self {
+        FixedBuffer {
+            raw: [0; N],
+            length: 0,
+        }
+    }
 
-    pub struct ReadWrapper<T>
-    where
-        T: io::Read,
-    {
-        raw: T,
+    pub fn len(&self) -> usize {
+        self.length
     }
 
-    impl<T> From<T> for ReadWrapper<T>
-    where
-        T: io::Read,
-    {
-        fn from(v: T) -> Self {
-            ReadWrapper { raw: v }
-        }
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
     }
 
-    impl<T> ReadWrapper<T>
-    where
-        T: io::Read,
-    {
-        pub fn into_inner(self) -> T {
-            self.raw
-        }
+    pub fn as_slice(&self) -> &[u8] {
+        &self.raw[..self.length]
     }
 
-    impl<'de, T> Read<'de> for ReadWrapper<T>
-    where
-        T: io::Read,
-    {
-        type Error = io::Error;
+    pub fn into_inner(self) -> [u8; N] {
+        self.raw
+    }
+}
 
-        fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
-            let _ = length;
-            None
-        }
+impl<const N: usize> Default for FixedBuffer<N> {
+    fn default() -> Self {
+        FixedBuffer::new()
+    }
+}
 
-        fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
-        where
-            B: AsMut<[u8]>,
-        {
-            self.raw.read_exact(&mut buffer.as_mut()[0..length])
-        }
+impl<const N: usize> Write for FixedBuffer<N> {
+    type Error = IoError;
 
-        fn is(&self) -> Option<()> {
-            Some(())
-        }
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let length = &mut self.length;
+        sub_slice_mut(&mut self.raw, *length, bytes.len())
+            .map(|target| target.copy_from_slice(bytes))
+            .map(|()| *length += bytes.len())
     }
+}
 
-    pub struct WriteWrapper<T>
-    where
-        T: io::Write,
-    {
-        raw: T,
+/// A bounded vector, a write that does not fit in the capacity fails as a whole.
+#[cfg(feature = "heapless")]
+impl<const N: usize> Write for heapless::Vec<u8, N> {
+    type Error = IoError;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let available = N - self.len();
+        self.extend_from_slice(bytes)
+            .map_err(|()| IoError { missing: available..bytes.len() })
     }
+}
 
-    impl<T> From<T> for WriteWrapper<T>
-    where
-        T: io::Write,
-    {
-        fn from(v: T) -> Self {
-            WriteWrapper { raw: v }
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cursor<T> {
+    inner: T,
+    position: usize,
+}
+
+impl<T> Cursor<T> {
+    pub fn new(inner: T) -> Self {
+        Cursor {
+            inner,
+            position: 0,
         }
     }
 
-    impl<T> WriteWrapper<T>
-    where
-        T: io::Write,
-    {
-        pub fn into_inner(self) -> T {
-            self.raw
-        }
+    pub fn position(&self) -> usize {
+        self.position
     }
 
-    impl<T> Write for WriteWrapper<T>
-    where
-        T: io::Write,
-    {
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> From<T> for Cursor<T> {
+    fn from(v: T) -> Self {
+        Cursor::new(v)
+    }
+}
+
+impl<'de> Read<'de> for Cursor<&'de [u8]> {
+    type Error = IoError;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let inner: &'de [u8] = self.inner;
+        let position = &mut self.position;
+        Some(sub_slice(inner, *position, length).inspect(|_| *position += length))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        let inner: &'de [u8] = self.inner;
+        sub_slice_mut(buffer.as_mut(), 0, length)
+            .and_then(|target| sub_slice(inner, self.position, length).map(|s| target.copy_from_slice(s)))
+            .map(|()| self.position += length)
+    }
+
+    fn is(&mut self) -> Option<()> {
+        if self.position < self.inner.len() {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.inner.len().saturating_sub(self.position))
+    }
+}
+
+impl Write for Cursor<&mut [u8]> {
+    type Error = IoError;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let position = &mut self.position;
+        sub_slice_mut(self.inner, *position, bytes.len())
+            .map(|target| target.copy_from_slice(bytes))
+            .map(|()| *position += bytes.len())
+    }
+}
+
+/// Forwards to the inner writer and tracks the absolute offset of the stream.
+///
+/// Start from the length of the existing data to append to it, the offsets
+/// observed between values can then be stored as an index.
+#[derive(Debug, Clone)]
+pub struct OffsetWrite<W>
+where
+    W: Write,
+{
+    inner: W,
+    start: usize,
+    position: usize,
+}
+
+impl<W> OffsetWrite<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W) -> Self {
+        OffsetWrite::with_position(inner, 0)
+    }
+
+    pub fn with_position(inner: W, position: usize) -> Self {
+        OffsetWrite { inner, start: position, position }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of bytes written through this writer, not counting the start position.
+    pub fn bytes_written(&self) -> usize {
+        self.position - self.start
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> From<W> for OffsetWrite<W>
+where
+    W: Write,
+{
+    fn from(v: W) -> Self {
+        OffsetWrite::new(v)
+    }
+}
+
+impl<W> Write for OffsetWrite<W>
+where
+    W: Write,
+{
+    type Error = W::Error;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let position = &mut self.position;
+        self.inner.write(bytes).map(|()| *position += bytes.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Counts the bytes read through it, and remembers where the latest read started.
+///
+/// When the deserializer fails, the latest read is the one that failed or that returned
+/// the bytes it rejected, so `last_offset` points at the culprit.
+#[derive(Debug, Clone)]
+pub struct OffsetRead<R> {
+    inner: R,
+    position: usize,
+    last: usize,
+}
+
+impl<R> OffsetRead<R> {
+    pub fn new(inner: R) -> Self {
+        OffsetRead { inner, position: 0, last: 0 }
+    }
+
+    /// The number of bytes consumed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The offset where the latest read started.
+    pub fn last_offset(&self) -> usize {
+        self.last
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> From<R> for OffsetRead<R> {
+    fn from(v: R) -> Self {
+        OffsetRead::new(v)
+    }
+}
+
+impl<'de, R> Read<'de> for OffsetRead<R>
+where
+    R: Read<'de>,
+{
+    type Error = R::Error;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        self.last = self.position;
+        let position = &mut self.position;
+        self.inner.read(length)
+            .map(|x| x.inspect(|_| *position += length))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        self.last = self.position;
+        let position = &mut self.position;
+        self.inner.read_in_buffer(buffer, length)
+            .map(|()| *position += length)
+    }
+
+    fn is(&mut self) -> Option<()> {
+        self.inner.is()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+}
+
+/// Discards the bytes and counts them, to learn the size of an encoding without a buffer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CountingWrite {
+    count: usize,
+}
+
+impl CountingWrite {
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Write for CountingWrite {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.count += bytes.len();
+        Ok(())
+    }
+}
+
+/// Forwards to the inner writer until `limit` bytes are written, then fails.
+///
+/// A write that does not fit fails as a whole, so the inner writer never holds more than
+/// `limit` bytes, and the serializer stops at the first value that exceeds it.
+#[derive(Debug, Clone)]
+pub struct LimitWrite<W>
+where
+    W: Write,
+{
+    inner: W,
+    limit: usize,
+}
+
+impl<W> LimitWrite<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W, limit: usize) -> Self {
+        LimitWrite { inner, limit }
+    }
+
+    /// The number of bytes that can still be written.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> Write for LimitWrite<W>
+where
+    W: Write,
+{
+    type Error = Either<IoError, W::Error>;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        if self.limit < bytes.len() {
+            Err(Either::Left(IoError { missing: self.limit..bytes.len() }))
+        } else {
+            let limit = &mut self.limit;
+            self.inner.write(bytes)
+                .map_err(Either::Right)
+                .map(|()| *limit -= bytes.len())
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().map_err(Either::Right)
+    }
+}
+
+/// A write of `attempted` bytes did not fit in the `remaining` budget of a `BoundedWrite`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BudgetExceeded {
+    pub attempted: usize,
+    pub remaining: usize,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot write {} bytes, {} bytes left in the budget", self.attempted, self.remaining)
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl std::error::Error for BudgetExceeded {}
+
+/// Forwards to the inner writer while the total stays within `max` bytes, for example the
+/// payload of a packet.
+///
+/// Unlike `LimitWrite` the error tells how much was attempted and how much was left. A
+/// write that does not fit fails as a whole, nothing of it reaches the inner writer.
+#[derive(Debug, Clone)]
+pub struct BoundedWrite<W>
+where
+    W: Write,
+{
+    inner: W,
+    max: usize,
+    written: usize,
+}
+
+impl<W> BoundedWrite<W>
+where
+    W: Write,
+{
+    pub fn new(inner: W, max: usize) -> Self {
+        BoundedWrite { inner, max, written: 0 }
+    }
+
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.max - self.written
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> Write for BoundedWrite<W>
+where
+    W: Write,
+{
+    type Error = Either<BudgetExceeded, W::Error>;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let remaining = self.remaining();
+        if remaining < bytes.len() {
+            Err(Either::Left(BudgetExceeded { attempted: bytes.len(), remaining }))
+        } else {
+            let written = &mut self.written;
+            self.inner.write(bytes)
+                .map_err(Either::Right)
+                .map(|()| *written += bytes.len())
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().map_err(Either::Right)
+    }
+}
+
+/// Forwards every write to both `A` and `B`, for example to a file and to a hasher.
+///
+/// `B` is not written if writing to `A` fails, and `A` already holds the bytes if
+/// writing to `B` fails.
+#[derive(Debug, Clone)]
+pub struct TeeWrite<A, B>
+where
+    A: Write,
+    B: Write,
+{
+    first: A,
+    second: B,
+}
+
+impl<A, B> TeeWrite<A, B>
+where
+    A: Write,
+    B: Write,
+{
+    pub fn new(first: A, second: B) -> Self {
+        TeeWrite { first, second }
+    }
+
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B> Write for TeeWrite<A, B>
+where
+    A: Write,
+    B: Write,
+{
+    type Error = Either<A::Error, B::Error>;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.first.write(bytes).map_err(Either::Left)?;
+        self.second.write(bytes).map_err(Either::Right)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.first.flush().map_err(Either::Left)?;
+        self.second.flush().map_err(Either::Right)
+    }
+}
+
+/// Reads `A` until it ends, then `B`, like `std::io::Read::chain`, for example a header
+/// that is already in memory followed by a stream.
+///
+/// A read that fits in `A` or starts after its end is borrowed if the reader allows it,
+/// a read across the end of `A` is copied into the buffer of the deserializer. If `A`
+/// does not know how many bytes it has left, like `ReadWrapper`, nothing is borrowed
+/// until it ends and its bytes are read one at a time while `is` finds more.
+#[derive(Debug, Clone)]
+pub struct ChainRead<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> ChainRead<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        ChainRead { first, second }
+    }
+
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<'de, A, B> ChainRead<A, B>
+where
+    A: Read<'de>,
+    B: Read<'de>,
+{
+    // how many of the next `length` bytes come from `A`, `None` if `A` cannot tell
+    fn split(&mut self, length: usize) -> Option<usize> {
+        match self.first.is() {
+            None => Some(0),
+            Some(()) => self.first.remaining().map(|remaining| remaining.min(length)),
+        }
+    }
+}
+
+impl<'de, A, B> Read<'de> for ChainRead<A, B>
+where
+    A: Read<'de>,
+    B: Read<'de>,
+{
+    type Error = Either<A::Error, B::Error>;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        match self.split(length) {
+            Some(0) => self.second.read(length).map(|x| x.map_err(Either::Right)),
+            Some(head) if head == length => self.first.read(length).map(|x| x.map_err(Either::Left)),
+            _ => None,
+        }
+    }
+
+    fn read_in_buffer<T>(&mut self, buffer: &mut T, length: usize) -> Result<(), Self::Error>
+    where
+        T: AsMut<[u8]>,
+    {
+        let buffer = buffer.as_mut();
+        let end = buffer.len();
+        let head = match self.split(length) {
+            Some(head) => {
+                if head != 0 {
+                    let mut first = &mut buffer[..head.min(end)];
+                    self.first.read_in_buffer(&mut first, head).map_err(Either::Left)?;
+                }
+                head
+            },
+            None => {
+                let mut head = 0;
+                while head < length && self.first.is().is_some() {
+                    let mut first = &mut buffer[head.min(end)..];
+                    self.first.read_in_buffer(&mut first, 1).map_err(Either::Left)?;
+                    head += 1;
+                }
+                head
+            },
+        };
+        if head != length {
+            let mut second = &mut buffer[head.min(end)..];
+            self.second.read_in_buffer(&mut second, length - head).map_err(Either::Right)?;
+        }
+        Ok(())
+    }
+
+    fn is(&mut self) -> Option<()> {
+        self.first.is().or_else(|| self.second.is())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.first.remaining().and_then(|first| self.second.remaining().map(|second| first + second))
+    }
+}
+
+pub trait BinarySerializerDelegate {
+    type Variant: ser::Serialize;
+    type Length: ser::Serialize;
+    type SequenceLength: ser::Serialize;
+    type Char: ser::Serialize;
+
+    fn encode_variant(v: u32) -> Self::Variant;
+    /// The length of a string or a byte array.
+    fn encode_length(v: usize) -> Self::Length;
+    /// The count of elements of a sequence or of entries of a map.
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength;
+    fn encode_char(v: char) -> Self::Char;
+
+    fn length_prefixed_variants() -> bool {
+        false
+    }
+
+    /// The bytes of `()` and of a unit struct.
+    fn encode_unit() -> &'static [u8] {
+        &[]
+    }
+
+    /// Whether every primitive starts with its tag, see `PrimitiveTagDelegate`.
+    fn primitive_tags() -> bool {
+        false
+    }
+
+    /// Whether repeated strings refer to their first occurrence, see `InternedStringDelegate`.
+    fn interned_strings() -> bool {
+        false
+    }
+
+    /// Whether structs start with the bitmap of their present options, see `OptionBitmapDelegate`.
+    fn option_bitmap() -> bool {
+        false
+    }
+
+    /// The tag written for the variant `name` whose index is `index`.
+    fn variant_tag(index: u32, name: &'static str) -> u32 {
+        let _ = name;
+        index
+    }
+
+    fn write_integer<W, E>(write: &mut W, v: u64, size: usize) -> Result<(), W::Error>
+    where
+        W: Write,
+        E: ByteOrder,
+    {
+        let mut buffer = [0; 8];
+        E::write_uint(&mut buffer, v, size);
+        write.write(&buffer[..size])
+    }
+}
+
+/// Implements the methods `$method` of `BinarySerializerDelegate` and its associated types
+/// by calling the delegate `$h` that the wrapper wraps, the wrapper writes the rest itself.
+macro_rules! forward_serializer_delegate {
+    (@ $h:ident types) => {
+        type Variant = $h::Variant;
+        type Length = $h::Length;
+        type SequenceLength = $h::SequenceLength;
+        type Char = $h::Char;
+    };
+    (@ $h:ident encode_variant) => {
+        fn encode_variant(v: u32) -> Self::Variant {
+            $h::encode_variant(v)
+        }
+    };
+    (@ $h:ident encode_length) => {
+        fn encode_length(v: usize) -> Self::Length {
+            $h::encode_length(v)
+        }
+    };
+    (@ $h:ident encode_sequence_length) => {
+        fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+            $h::encode_sequence_length(v)
+        }
+    };
+    (@ $h:ident encode_char) => {
+        fn encode_char(v: char) -> Self::Char {
+            $h::encode_char(v)
+        }
+    };
+    (@ $h:ident length_prefixed_variants) => {
+        fn length_prefixed_variants() -> bool {
+            $h::length_prefixed_variants()
+        }
+    };
+    (@ $h:ident encode_unit) => {
+        fn encode_unit() -> &'static [u8] {
+            $h::encode_unit()
+        }
+    };
+    (@ $h:ident primitive_tags) => {
+        fn primitive_tags() -> bool {
+            $h::primitive_tags()
+        }
+    };
+    (@ $h:ident interned_strings) => {
+        fn interned_strings() -> bool {
+            $h::interned_strings()
+        }
+    };
+    (@ $h:ident option_bitmap) => {
+        fn option_bitmap() -> bool {
+            $h::option_bitmap()
+        }
+    };
+    (@ $h:ident variant_tag) => {
+        fn variant_tag(index: u32, name: &'static str) -> u32 {
+            $h::variant_tag(index, name)
+        }
+    };
+    (@ $h:ident write_integer) => {
+        fn write_integer<W, E>(write: &mut W, v: u64, size: usize) -> Result<(), W::Error>
+        where
+            W: $crate::io::Write,
+            E: byteorder::ByteOrder,
+        {
+            $h::write_integer::<W, E>(write, v, size)
+        }
+    };
+    ($h:ident; $($method:ident),* $(,)?) => {
+        $(forward_serializer_delegate!(@ $h $method);)*
+    };
+}
+
+pub(crate) use forward_serializer_delegate;
+
+/// Implements the methods `$method` of `BinaryDeserializerDelegate` and its associated type
+/// by calling the delegate `$h` that the wrapper wraps, the wrapper writes the rest itself.
+macro_rules! forward_deserializer_delegate {
+    (@ $h:ident types) => {
+        type SmallBuffer = $h::SmallBuffer;
+    };
+    (@ $h:ident variant_size) => {
+        fn variant_size() -> usize {
+            $h::variant_size()
+        }
+    };
+    (@ $h:ident length_size) => {
+        fn length_size() -> usize {
+            $h::length_size()
+        }
+    };
+    (@ $h:ident sequence_length_size) => {
+        fn sequence_length_size() -> usize {
+            $h::sequence_length_size()
+        }
+    };
+    (@ $h:ident char_size) => {
+        fn char_size() -> usize {
+            $h::char_size()
+        }
+    };
+    (@ $h:ident decode_variant) => {
+        fn decode_variant<E>(bytes: &[u8]) -> u32 where E: byteorder::ByteOrder {
+            $h::decode_variant::<E>(bytes)
+        }
+    };
+    (@ $h:ident decode_length) => {
+        fn decode_length<E>(bytes: &[u8]) -> usize where E: byteorder::ByteOrder {
+            $h::decode_length::<E>(bytes)
+        }
+    };
+    (@ $h:ident decode_sequence_length) => {
+        fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: byteorder::ByteOrder {
+            $h::decode_sequence_length::<E>(bytes)
+        }
+    };
+    (@ $h:ident decode_char) => {
+        fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: byteorder::ByteOrder {
+            $h::decode_char::<E>(bytes)
+        }
+    };
+    (@ $h:ident length_prefixed_variants) => {
+        fn length_prefixed_variants() -> bool {
+            $h::length_prefixed_variants()
+        }
+    };
+    (@ $h:ident unit_size) => {
+        fn unit_size() -> usize {
+            $h::unit_size()
+        }
+    };
+    (@ $h:ident decode_unit) => {
+        fn decode_unit(bytes: &[u8]) -> bool {
+            $h::decode_unit(bytes)
+        }
+    };
+    (@ $h:ident primitive_tags) => {
+        fn primitive_tags() -> bool {
+            $h::primitive_tags()
+        }
+    };
+    (@ $h:ident interned_strings) => {
+        fn interned_strings() -> bool {
+            $h::interned_strings()
+        }
+    };
+    (@ $h:ident option_bitmap) => {
+        fn option_bitmap() -> bool {
+            $h::option_bitmap()
+        }
+    };
+    (@ $h:ident variant_index) => {
+        fn variant_index(tag: u32, variants: &'static [&'static str]) -> Option<u32> {
+            $h::variant_index(tag, variants)
+        }
+    };
+    (@ $h:ident $method:ident -> $ty:ty, $($arg:ident: $arg_ty:ty),*) => {
+        fn $method<'de, R, E>(read: &mut R $(, $arg: $arg_ty)*) -> Result<$ty, either::Either<$crate::de::BinaryDeserializerError, R::Error>>
+        where
+            R: $crate::io::Read<'de>,
+            E: byteorder::ByteOrder,
+        {
+            $h::$method::<R, E>(read $(, $arg)*)
+        }
+    };
+    (@ $h:ident read_integer) => {
+        forward_deserializer_delegate!(@ $h read_integer -> u64, size: usize);
+    };
+    (@ $h:ident read_signed_integer) => {
+        forward_deserializer_delegate!(@ $h read_signed_integer -> i64, size: usize);
+    };
+    (@ $h:ident read_variant) => {
+        forward_deserializer_delegate!(@ $h read_variant -> u32,);
+    };
+    (@ $h:ident read_length) => {
+        forward_deserializer_delegate!(@ $h read_length -> usize,);
+    };
+    (@ $h:ident read_sequence_length) => {
+        forward_deserializer_delegate!(@ $h read_sequence_length -> Option<usize>,);
+    };
+    (@ $h:ident read_char) => {
+        forward_deserializer_delegate!(@ $h read_char -> char,);
+    };
+    ($h:ident; $($method:ident),* $(,)?) => {
+        $(forward_deserializer_delegate!(@ $h $method);)*
+    };
+}
+
+pub(crate) use forward_deserializer_delegate;
+
+pub struct DefaultBinarySerializerDelegate;
+
+impl BinarySerializerDelegate for DefaultBinarySerializerDelegate {
+    type Variant = u32;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        v
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        v
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+#[cfg(feature = "use_std")]
+pub use self::with_std::{WriteWrapper, ReadWrapper, BufReadWrapper, BorrowBufRead, BufferedWriteWrapper};
+
+#[cfg(feature = "use_std")]
+pub(crate) use self::with_std::{VecWrite, CaptureRead};
+
+#[cfg(feature = "use_std")]
+pub use self::with_std::PatchWrite;
+
+#[cfg(feature = "use_std")]
+mod with_std {
+    use super::{Write, Read, IoError};
+    use std::{io, convert::Infallible, collections::HashMap};
+
+    /// Reads from any `std::io::Read`, including a `BufReader`, by copying.
+    ///
+    /// It cannot give borrowed `&str` or `&[u8]`. A borrowed value must stay valid as long
+    /// as the input, `'de`, while the buffer of a `BufRead` is overwritten by the next
+    /// `fill_buf`. A string is copied into a temporary, which is enough for `String` or
+    /// `Cow<str>`, bytes fail with `CannotReadBorrowed`. To borrow, read the whole input
+    /// into a `Vec<u8>`, or map the file, and deserialize from the slice, or use
+    /// `BufReadWrapper` over a reader of memory.
+    ///
+    /// `is` reads one byte ahead to tell the end of the stream, the byte is kept for the
+    /// next read.
+    #[derive(Debug)]
+    pub struct ReadWrapper<T>
+    where
+        T: io::Read,
+    {
+        raw: T,
+        peeked: Option<u8>,
+    }
+
+    impl<T> From<T> for ReadWrapper<T>
+    where
+        T: io::Read,
+    {
+        fn from(v: T) -> Self {
+            ReadWrapper { raw: v, peeked: None }
+        }
+    }
+
+    impl<T> ReadWrapper<T>
+    where
+        T: io::Read,
+    {
+        /// The underlying reader, a byte read ahead by `is` is lost.
+        pub fn into_inner(self) -> T {
+            self.raw
+        }
+    }
+
+    impl<'de, T> Read<'de> for ReadWrapper<T>
+    where
+        T: io::Read,
+    {
+        type Error = io::Error;
+
+        fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+            let _ = length;
+            None
+        }
+
+        fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+        where
+            B: AsMut<[u8]>,
+        {
+            let peeked = &mut self.peeked;
+            let raw = &mut self.raw;
+            buffer.as_mut()
+                .get_mut(..length)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "buffer is too small"))
+                .and_then(|target| match (peeked.take(), target.split_first_mut()) {
+                    (Some(byte), Some((first, rest))) => {
+                        *first = byte;
+                        raw.read_exact(rest)
+                    },
+                    (byte, _) => {
+                        *peeked = byte;
+                        raw.read_exact(target)
+                    },
+                })
+        }
+
+        // a failed read is reported by the next read, not here
+        fn is(&mut self) -> Option<()> {
+            if self.peeked.is_some() {
+                return Some(());
+            }
+            let mut byte = [0];
+            loop {
+                match self.raw.read(&mut byte) {
+                    Ok(0) => return None,
+                    Ok(_) => {
+                        self.peeked = Some(byte[0]);
+                        return Some(());
+                    },
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                    Err(_) => return Some(()),
+                }
+            }
+        }
+    }
+
+    /// A `BufRead` whose bytes live as long as `'de`, so `BufReadWrapper` lends them.
+    pub trait BorrowBufRead<'de>: io::BufRead {
+        /// Consumes the next `length` bytes and returns them, `None` if they are not
+        /// available for `'de`, then they are copied by `io::Read`.
+        fn lend(&mut self, length: usize) -> Option<&'de [u8]>;
+
+        /// The number of bytes left.
+        fn remaining(&self) -> usize;
+    }
+
+    impl<'de> BorrowBufRead<'de> for &'de [u8] {
+        fn lend(&mut self, length: usize) -> Option<&'de [u8]> {
+            let slice: &'de [u8] = self;
+            slice.get(..length).inspect(|_| *self = &slice[length..])
+        }
+
+        fn remaining(&self) -> usize {
+            self.len()
+        }
+    }
+
+    impl<'de> BorrowBufRead<'de> for io::Cursor<&'de [u8]> {
+        fn lend(&mut self, length: usize) -> Option<&'de [u8]> {
+            let slice: &'de [u8] = self.get_ref();
+            let position = self.position() as usize;
+            slice
+                .get(position..)
+                .and_then(|rest| rest.get(..length))
+                .inspect(|_| self.set_position((position + length) as u64))
+        }
+
+        fn remaining(&self) -> usize {
+            self.get_ref().len().saturating_sub(self.position() as usize)
+        }
+    }
+
+    // the bytes are lent only when nothing is buffered, those are not `'de`
+    impl<'de> BorrowBufRead<'de> for io::BufReader<&'de [u8]> {
+        fn lend(&mut self, length: usize) -> Option<&'de [u8]> {
+            if self.buffer().is_empty() {
+                self.get_mut().lend(length)
+            } else {
+                None
+            }
+        }
+
+        fn remaining(&self) -> usize {
+            self.buffer().len() + self.get_ref().len()
+        }
+    }
+
+    /// Reads from a `BufRead` over memory and lends borrowed `&str` and `&[u8]` from it.
+    ///
+    /// A borrowed value must stay valid as long as `'de`, so the bytes are lent only by a
+    /// reader that holds a `&'de [u8]`, see `BorrowBufRead`. A `BufReader` lends them
+    /// while its own buffer is empty, so the wrapper reads past its buffer rather than
+    /// fills it. The bytes that are not available for `'de` are copied.
+    ///
+    /// A `BufReader` over a file can never lend, its buffer is refilled by the next read,
+    /// so it does not implement `BorrowBufRead`. Map the file or read it into a `Vec<u8>`
+    /// to borrow from it.
+    #[derive(Debug)]
+    pub struct BufReadWrapper<T>
+    where
+        T: io::BufRead,
+    {
+        raw: T,
+    }
+
+    impl<T> From<T> for BufReadWrapper<T>
+    where
+        T: io::BufRead,
+    {
+        fn from(v: T) -> Self {
+            BufReadWrapper { raw: v }
+        }
+    }
+
+    impl<T> BufReadWrapper<T>
+    where
+        T: io::BufRead,
+    {
+        pub fn get_ref(&self) -> &T {
+            &self.raw
+        }
+
+        pub fn into_inner(self) -> T {
+            self.raw
+        }
+    }
+
+    impl<'de, T> Read<'de> for BufReadWrapper<T>
+    where
+        T: BorrowBufRead<'de>,
+    {
+        type Error = io::Error;
+
+        fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+            self.raw.lend(length).map(Ok)
+        }
+
+        fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+        where
+            B: AsMut<[u8]>,
+        {
+            let target = buffer.as_mut()
+                .get_mut(..length)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "buffer is too small"))?;
+            match self.raw.lend(length) {
+                Some(bytes) => {
+                    target.copy_from_slice(bytes);
+                    Ok(())
+                },
+                None => self.raw.read_exact(target),
+            }
+        }
+
+        fn is(&mut self) -> Option<()> {
+            if self.raw.remaining() == 0 {
+                None
+            } else {
+                Some(())
+            }
+        }
+
+        fn remaining(&self) -> Option<usize> {
+            Some(self.raw.remaining())
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct WriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        raw: T,
+    }
+
+    impl<T> From<T> for WriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        fn from(v: T) -> Self {
+            WriteWrapper { raw: v }
+        }
+    }
+
+    impl<T> WriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        pub fn get_ref(&self) -> &T {
+            &self.raw
+        }
+
+        pub fn get_mut(&mut self) -> &mut T {
+            &mut self.raw
+        }
+
+        pub fn into_inner(self) -> T {
+            self.raw
+        }
+    }
+
+    impl<T> Write for WriteWrapper<T>
+    where
+        T: io::Write,
+    {
         type Error = io::Error;
 
         fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
             io::Write::write_all(&mut self.raw, bytes)
         }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            io::Write::flush(&mut self.raw)
+        }
+    }
+
+    /// Appends to a borrowed vector, the bytes already in it stay in front.
+    impl Write for &mut Vec<u8> {
+        type Error = Infallible;
+
+        fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+
+    /// Collects small writes in memory and passes them to the inner writer in bulk.
+    ///
+    /// The rest of the buffer is written by `flush`, `into_inner` or on drop, where
+    /// an error is ignored, so call `into_inner` to observe it.
+    #[derive(Debug)]
+    pub struct BufferedWriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        raw: Option<T>,
+        buffer: Vec<u8>,
+    }
+
+    impl<T> From<T> for BufferedWriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        fn from(v: T) -> Self {
+            BufferedWriteWrapper::with_capacity(DEFAULT_BUFFER_CAPACITY, v)
+        }
+    }
+
+    impl<T> BufferedWriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        pub fn with_capacity(capacity: usize, raw: T) -> Self {
+            BufferedWriteWrapper {
+                raw: Some(raw),
+                buffer: Vec::with_capacity(capacity),
+            }
+        }
+
+        pub fn flush(&mut self) -> Result<(), io::Error> {
+            self.flush_buffer()
+                .and_then(|()| self.raw.as_mut().map_or(Ok(()), io::Write::flush))
+        }
+
+        /// Flushes the buffer and returns the writer, on failure the wrapper comes back
+        /// with the error and keeps the bytes not written yet, like `io::BufWriter`.
+        pub fn into_inner(mut self) -> Result<T, (Self, io::Error)> {
+            match self.flush() {
+                Ok(()) => self.raw.take().ok_or_else(|| {
+                    let e = io::Error::other("the writer is already taken");
+                    (self, e)
+                }),
+                Err(e) => Err((self, e)),
+            }
+        }
+
+        fn flush_buffer(&mut self) -> Result<(), io::Error> {
+            let buffer = &mut self.buffer;
+            match self.raw {
+                Some(ref mut raw) if !buffer.is_empty() => {
+                    raw.write_all(buffer).map(|()| buffer.clear())
+                },
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl<T> Write for BufferedWriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        type Error = io::Error;
+
+        fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            if self.buffer.len() + bytes.len() > self.buffer.capacity() {
+                self.flush_buffer()?;
+            }
+            match self.raw {
+                Some(ref mut raw) if bytes.len() >= self.buffer.capacity() => raw.write_all(bytes),
+                _ => {
+                    self.buffer.extend_from_slice(bytes);
+                    Ok(())
+                },
+            }
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            BufferedWriteWrapper::flush(self)
+        }
+    }
+
+    impl<T> Drop for BufferedWriteWrapper<T>
+    where
+        T: io::Write,
+    {
+        fn drop(&mut self) {
+            let _ = self.flush_buffer();
+        }
+    }
+
+    /// Keeps a copy of every byte read through it.
+    pub(crate) struct CaptureRead<R> {
+        pub inner: R,
+        pub captured: Vec<u8>,
+    }
+
+    impl<'de, R> Read<'de> for CaptureRead<R>
+    where
+        R: Read<'de>,
+    {
+        type Error = R::Error;
+
+        fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+            let captured = &mut self.captured;
+            self.inner.read(length)
+                .map(|x| x.inspect(|bytes| captured.extend_from_slice(bytes)))
+        }
+
+        fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+        where
+            B: AsMut<[u8]>,
+        {
+            self.inner.read_in_buffer(buffer, length)
+                .map(|()| self.captured.extend_from_slice(&buffer.as_mut()[..length]))
+        }
+
+        fn is(&mut self) -> Option<()> {
+            self.inner.is()
+        }
+
+        fn remaining(&self) -> Option<usize> {
+            self.inner.remaining()
+        }
+    }
+
+    #[derive(Default)]
+    pub(crate) struct VecWrite(pub Vec<u8>);
+
+    impl Write for VecWrite {
+        type Error = Infallible;
+
+        fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.0.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Keeps the output in memory and remembers named positions in it, so bytes written
+    /// as a placeholder can be overwritten once their value is known.
+    #[derive(Debug, Default, Clone)]
+    pub struct PatchWrite {
+        bytes: Vec<u8>,
+        marks: HashMap<String, usize>,
+    }
+
+    impl From<Vec<u8>> for PatchWrite {
+        fn from(v: Vec<u8>) -> Self {
+            PatchWrite { bytes: v, marks: HashMap::new() }
+        }
+    }
+
+    impl PatchWrite {
+        pub fn position(&self) -> usize {
+            self.bytes.len()
+        }
+
+        /// Remembers the current position as `name`, replacing an older mark of that name.
+        pub fn mark(&mut self, name: &str) {
+            self.marks.insert(name.to_owned(), self.bytes.len());
+        }
+
+        pub fn offset(&self, name: &str) -> Option<usize> {
+            self.marks.get(name).cloned()
+        }
+
+        /// Overwrites the bytes at `offset`, fails if they would go past the written end.
+        pub fn patch_at(&mut self, offset: usize, bytes: &[u8]) -> Result<(), IoError> {
+            let available = self.bytes.len().saturating_sub(offset);
+            self.bytes
+                .get_mut(offset..)
+                .and_then(|rest| rest.get_mut(..bytes.len()))
+                .map(|target| target.copy_from_slice(bytes))
+                .ok_or(IoError { missing: available..bytes.len() })
+        }
+
+        pub fn into_inner(self) -> Vec<u8> {
+            self.bytes
+        }
+    }
+
+    impl Write for PatchWrite {
+        type Error = IoError;
+
+        fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.bytes.extend_from_slice(bytes);
+            Ok(())
+        }
     }
 }
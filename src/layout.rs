@@ -0,0 +1,260 @@
+use serde::ser::{self, Serialize, Serializer, SerializeStruct, Impossible};
+use super::{
+    ser::{BinarySerializerError, serialized_size},
+    err::ErrorAdapter,
+};
+
+pub type LayoutError = ErrorAdapter<BinarySerializerError, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// Reports the offset and size of every field of the struct `value` in the default encoding.
+///
+/// The layout is recorded while serializing `value`, every field is measured with the
+/// default byte order and delegate, and the struct itself adds no length or padding. The
+/// fields of a fixed layout report the same sizes for any value, a variable-length field
+/// reports the size it has in `value`, such a struct has no fixed layout.
+pub fn layout_of<T>(value: &T) -> Result<Vec<FieldLayout>, LayoutError>
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(Layout)
+}
+
+struct Layout;
+
+fn not_a_struct() -> LayoutError {
+    ser::Error::custom("the layout is only defined for a struct")
+}
+
+impl Serializer for Layout {
+    type Ok = Vec<FieldLayout>;
+    type Error = LayoutError;
+
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(not_a_struct())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = value;
+        Err(not_a_struct())
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(not_a_struct())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        let _ = name;
+        Err(not_a_struct())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let _ = (name, variant_index, variant);
+        Err(not_a_struct())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = name;
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = (name, variant_index, variant, value);
+        Err(not_a_struct())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let _ = len;
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        let _ = len;
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let _ = (name, len);
+        Err(not_a_struct())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let _ = (name, variant_index, variant, len);
+        Err(not_a_struct())
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let _ = len;
+        Err(not_a_struct())
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let _ = name;
+        Ok(Fields {
+            offset: 0,
+            layout: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let _ = (name, variant_index, variant, len);
+        Err(not_a_struct())
+    }
+}
+
+/// Measures the fields one by one and records where each of them starts.
+struct Fields {
+    offset: usize,
+    layout: Vec<FieldLayout>,
+}
+
+impl SerializeStruct for Fields {
+    type Ok = Vec<FieldLayout>;
+    type Error = LayoutError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let size = serialized_size(value)?;
+        self.layout.push(FieldLayout {
+            name: key,
+            offset: self.offset,
+            size,
+        });
+        self.offset += size;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.layout)
+    }
+}
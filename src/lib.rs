@@ -5,6 +5,37 @@ mod ser;
 mod de;
 mod io;
 mod err;
+mod varint;
+mod compat;
+mod endian;
+mod prefixed;
+mod hashed;
+mod primitive;
+mod interned;
+mod bitmap;
+mod protobuf;
+mod portable;
+mod compact;
+mod utf8;
+mod tagged;
+mod progress;
+mod cobs;
+mod crc;
+mod framed;
+mod builder;
+mod readable;
+#[cfg(feature = "use_std")]
+mod layout;
+#[cfg(feature = "use_std")]
+pub mod instant_as_duration;
+#[cfg(feature = "use_std")]
+pub mod net;
+#[cfg(feature = "use_std")]
+pub mod weak;
+#[cfg(feature = "use_std")]
+mod swap;
+#[cfg(feature = "use_std")]
+mod value;
 
 pub use self::ser::BinarySerializeSeq;
 pub use self::ser::BinarySerializeTuple;
@@ -16,29 +47,135 @@ pub use self::ser::BinarySerializeStructVariant;
 
 pub use self::ser::BinarySerializer;
 pub use self::ser::BinarySerializerError;
+pub use self::ser::to_slice_padded;
+pub use self::ser::serialized_size;
+pub use self::builder::SerializerBuilder;
+#[cfg(feature = "use_std")]
+pub use self::ser::{to_vec, to_vec_limited, ToVecError};
 
 pub use self::de::BinaryDeserializer;
 pub use self::de::BinaryDeserializerError;
+pub use self::de::SequenceAccess;
+#[cfg(feature = "use_std")]
+pub use self::de::{from_slice, from_slice_prefix, FromSliceError};
 
 #[cfg(feature = "use_std")]
-pub use self::io::{WriteWrapper, ReadWrapper};
+pub use self::io::{WriteWrapper, ReadWrapper, BufReadWrapper, BorrowBufRead, BufferedWriteWrapper, PatchWrite};
 
 pub use self::io::Write;
+pub use self::io::RingBuffer;
+pub use self::io::Cursor;
+pub use self::io::FixedBuffer;
+pub use self::io::OffsetWrite;
+pub use self::io::OffsetRead;
+pub use self::io::LimitWrite;
+pub use self::io::{BoundedWrite, BudgetExceeded};
+pub use self::io::CountingWrite;
+pub use self::io::TeeWrite;
+pub use self::io::ChainRead;
+pub use self::cobs::{CobsWrite, CobsRead};
+pub use self::crc::{Crc32Write, Crc32Read};
+#[cfg(feature = "use_std")]
+pub use self::framed::FramedWrite;
+pub use self::framed::FramedRead;
 pub use self::io::Read;
 pub use self::io::IoError;
+pub use self::io::END_MARKER;
 
 pub use self::io::BinarySerializerDelegate;
 pub use self::io::DefaultBinarySerializerDelegate;
 pub use self::io::BinaryDeserializerDelegate;
 pub use self::io::DefaultBinaryDeserializerDelegate;
 
+pub use self::varint::{VarintIntDelegate, VarintSerializerDelegate, VarintDeserializerDelegate, ZigzagVarintDelegate};
+pub use self::compat::SpeedyCompatDelegate;
+pub use self::prefixed::PrefixedVariantDelegate;
+pub use self::hashed::{NameHashDelegate, name_hash};
+pub use self::primitive::PrimitiveTagDelegate;
+pub use self::interned::InternedStringDelegate;
+pub use self::bitmap::OptionBitmapDelegate;
+pub use self::protobuf::ProtobufLengthDelegate;
+pub use self::protobuf::VarintLength;
+pub use self::tagged::TaggedIntDelegate;
+pub use self::portable::{PortableSerializerDelegate, PortableDeserializerDelegate, PortableLength};
+pub use self::compact::{CompactVariantDelegate, CompactVariant};
+pub use self::utf8::{Utf8CharDelegate, Utf8Char};
+
+pub use self::readable::{HumanReadable, Compact};
+
+pub use self::endian::Endianness;
+pub use self::endian::Endian;
+pub use self::endian::DefaultEndian;
+
+#[cfg(feature = "use_std")]
+pub use self::layout::{layout_of, FieldLayout, LayoutError};
+#[cfg(feature = "use_std")]
+pub use self::swap::{swap_endianness, transcode, SwapError};
+#[cfg(feature = "use_std")]
+pub use self::value::Value;
+
 pub use self::err::DisplayCollector;
+pub use self::err::DiscardCollector;
 pub use self::err::ErrorAdapter;
-
-use byteorder::NativeEndian;
+pub use self::err::ErrorAt;
 
 pub type DefaultBinarySerializer<W, D> =
-    BinarySerializer<W, NativeEndian, DefaultBinarySerializerDelegate, D>;
+    BinarySerializer<W, DefaultEndian, DefaultBinarySerializerDelegate, D>;
 
 pub type DefaultBinaryDeserializer<'de, R, D> =
-    BinaryDeserializer<'de, R, NativeEndian, DefaultBinaryDeserializerDelegate, D>;
+    BinaryDeserializer<'de, R, DefaultEndian, DefaultBinaryDeserializerDelegate, D>;
+
+pub type FixedSerializer<const N: usize, D> = DefaultBinarySerializer<FixedBuffer<N>, D>;
+
+/// Writes in little endian whatever the host is.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use serde::Serialize;
+/// use tirse::{LittleEndianSerializer, WriteWrapper};
+///
+/// let serializer = LittleEndianSerializer::<WriteWrapper<Vec<u8>>, String>::new(Vec::new());
+/// let bytes = 7u32.serialize(serializer).unwrap().consume().into_inner();
+/// assert_eq!(bytes, [7, 0, 0, 0]);
+/// # }
+/// ```
+pub type LittleEndianSerializer<W, D> =
+    BinarySerializer<W, byteorder::LittleEndian, DefaultBinarySerializerDelegate, D>;
+
+pub type LittleEndianDeserializer<'de, R, D> =
+    BinaryDeserializer<'de, R, byteorder::LittleEndian, DefaultBinaryDeserializerDelegate, D>;
+
+/// Writes in big endian whatever the host is.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use serde::Serialize;
+/// use tirse::{BigEndianSerializer, WriteWrapper};
+///
+/// let serializer = BigEndianSerializer::<WriteWrapper<Vec<u8>>, String>::new(Vec::new());
+/// let bytes = 7u32.serialize(serializer).unwrap().consume().into_inner();
+/// assert_eq!(bytes, [0, 0, 0, 7]);
+/// # }
+/// ```
+pub type BigEndianSerializer<W, D> =
+    BinarySerializer<W, byteorder::BigEndian, DefaultBinarySerializerDelegate, D>;
+
+pub type BigEndianDeserializer<'de, R, D> =
+    BinaryDeserializer<'de, R, byteorder::BigEndian, DefaultBinaryDeserializerDelegate, D>;
+
+/// The network byte order, big endian.
+pub type NetworkEndianSerializer<W, D> = BigEndianSerializer<W, D>;
+
+/// The network byte order, big endian.
+///
+/// ```
+/// # #[cfg(feature = "use_std")] {
+/// use serde::Deserialize;
+/// use tirse::NetworkEndianDeserializer;
+///
+/// let bytes = [0, 0, 1, 2];
+/// let deserializer = NetworkEndianDeserializer::<_, String>::new(bytes.iter());
+/// assert_eq!(u32::deserialize(deserializer).unwrap(), 0x0102);
+/// # }
+/// ```
+pub type NetworkEndianDeserializer<'de, R, D> = BigEndianDeserializer<'de, R, D>;
@@ -0,0 +1,90 @@
+//! Network byte order helpers.
+//!
+//! The encoding is big-endian, lengths of strings, byte arrays, sequences and maps are
+//! `u32`, enum and `Option` tags are `u32` and chars are `u32` code points. It does not
+//! depend on the platform, unlike the native endian, `usize` lengths of the default aliases.
+
+use serde::{Serialize, Deserialize};
+use byteorder::BigEndian;
+use either::Either;
+use super::{
+    io::{
+        IoError, VecWrite, BinarySerializerDelegate, BinaryDeserializerDelegate,
+        forward_deserializer_delegate,
+    },
+    ser::{BinarySerializer, ToVecError},
+    de::{BinaryDeserializer, BinaryDeserializerError},
+    portable::{PortableLength, PortableSerializerDelegate, PortableDeserializerDelegate},
+    err::ErrorAdapter,
+};
+
+/// The error of `from_slice`, the error of `to_vec` is `ToVecError`.
+pub type Error = ErrorAdapter<Either<BinaryDeserializerError, IoError>, String>;
+
+/// The delegate of the network encoding, for both the serializer and the deserializer.
+///
+/// Lengths are checked like with the portable delegates, serialization fails on a length
+/// above `u32::MAX` and deserialization on a length that does not fit in `usize`. The byte
+/// order is the parameter `E`, `to_vec` and `from_slice` pin it to big endian.
+pub struct NetworkDelegate;
+
+impl BinarySerializerDelegate for NetworkDelegate {
+    type Variant = u32;
+    type Length = PortableLength;
+    type SequenceLength = PortableLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        PortableSerializerDelegate::encode_length(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        PortableSerializerDelegate::encode_sequence_length(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+impl BinaryDeserializerDelegate for NetworkDelegate {
+    type SmallBuffer = [u8; 4];
+
+    forward_deserializer_delegate!(
+        PortableDeserializerDelegate;
+        variant_size, length_size, sequence_length_size, char_size, decode_variant,
+        decode_length, decode_sequence_length, decode_char, read_length,
+        read_sequence_length,
+    );
+}
+
+/// Encodes `value` in network byte order.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, ToVecError>
+where
+    T: ?Sized + Serialize,
+{
+    value
+        .serialize(BinarySerializer::<VecWrite, BigEndian, NetworkDelegate, String>::new(VecWrite::default()))
+        .map(|s| s.consume().0)
+        .map_err(|e| match e {
+            ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(e),
+            ErrorAdapter::Inner(Either::Right(e)) => match e {},
+            ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+        })
+}
+
+/// Decodes a `T` in network byte order, it may borrow strings and bytes from `bytes`.
+///
+/// Like `crate::from_slice` it fails if bytes are left after the value.
+pub fn from_slice<'a, T>(bytes: &'a [u8]) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let mut d = BinaryDeserializer::<_, BigEndian, NetworkDelegate, String>::new(bytes);
+    let value = T::deserialize(d.split())?;
+    d.finish().map(|()| value)
+}
@@ -0,0 +1,123 @@
+use core::convert::TryFrom;
+use byteorder::ByteOrder;
+use either::Either;
+use serde::{Serialize, Serializer, ser};
+use super::{
+    io::{Read, BinarySerializerDelegate, BinaryDeserializerDelegate, read_small},
+    de::BinaryDeserializerError,
+};
+
+/// A length written as `u32`, serialization fails if it does not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PortableLength(usize);
+
+impl Serialize for PortableLength {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        u32::try_from(self.0)
+            .map_err(|_| ser::Error::custom("length does not fit in u32"))
+            .and_then(|length| length.serialize(serializer))
+    }
+}
+
+/// Writes every length as `u32`, so the output does not depend on the width of `usize`.
+///
+/// The default delegate writes lengths as `usize`, which is 8 bytes on a 64-bit target and
+/// 4 bytes on a 32-bit one. Here a length is always 4 bytes, serialization fails on a
+/// length above `u32::MAX`, and `PortableDeserializerDelegate` fails on a length that does
+/// not fit in the `usize` of the reader.
+pub struct PortableSerializerDelegate;
+
+impl BinarySerializerDelegate for PortableSerializerDelegate {
+    type Variant = u32;
+    type Length = PortableLength;
+    type SequenceLength = PortableLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        PortableLength(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        PortableLength(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+/// Reads lengths written by `PortableSerializerDelegate`.
+pub struct PortableDeserializerDelegate;
+
+impl PortableDeserializerDelegate {
+    fn read_u32_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_small::<_, <Self as BinaryDeserializerDelegate>::SmallBuffer, _, _>(read, 4, E::read_u32)
+            .and_then(|length| usize::try_from(length)
+                .map_err(|_| Either::Left(BinaryDeserializerError::LengthOverflow(length)))
+            )
+    }
+}
+
+impl BinaryDeserializerDelegate for PortableDeserializerDelegate {
+    type SmallBuffer = [u8; 4];
+
+    fn variant_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn length_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn sequence_length_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn char_size() -> usize {
+        core::mem::size_of::<u32>()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        E::read_u32(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        E::read_u32(bytes) as usize
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        Some(E::read_u32(bytes) as usize)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        let code = E::read_u32(bytes);
+        core::char::from_u32(code).ok_or(code)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_u32_length::<R, E>(read)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_u32_length::<R, E>(read).map(Some)
+    }
+}
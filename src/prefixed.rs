@@ -0,0 +1,49 @@
+use core::marker;
+use super::io::{
+    BinarySerializerDelegate, BinaryDeserializerDelegate,
+    forward_serializer_delegate, forward_deserializer_delegate,
+};
+
+/// Wraps the delegate `H` and prefixes the payload of every enum variant with its length.
+///
+/// A reader that knows the variant but expects a shorter payload skips the trailing bytes,
+/// so fields can be appended to variants without breaking old readers. It costs one length
+/// per variant, including unit variants, and serialization buffers each payload in memory,
+/// so it requires the `use_std` feature.
+pub struct PrefixedVariantDelegate<H> {
+    phantom_data: marker::PhantomData<H>,
+}
+
+impl<H> BinarySerializerDelegate for PrefixedVariantDelegate<H>
+where
+    H: BinarySerializerDelegate,
+{
+    forward_serializer_delegate!(
+        H;
+        types,
+        encode_variant, encode_length, encode_sequence_length, encode_char, encode_unit,
+        primitive_tags, interned_strings, option_bitmap, variant_tag, write_integer,
+    );
+
+    fn length_prefixed_variants() -> bool {
+        true
+    }
+}
+
+impl<H> BinaryDeserializerDelegate for PrefixedVariantDelegate<H>
+where
+    H: BinaryDeserializerDelegate,
+{
+    forward_deserializer_delegate!(
+        H;
+        types,
+        variant_size, length_size, sequence_length_size, char_size, decode_variant,
+        decode_length, decode_sequence_length, decode_char, unit_size, decode_unit,
+        primitive_tags, interned_strings, option_bitmap, variant_index, read_integer,
+        read_signed_integer, read_variant, read_length, read_sequence_length, read_char,
+    );
+
+    fn length_prefixed_variants() -> bool {
+        true
+    }
+}
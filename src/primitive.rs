@@ -0,0 +1,72 @@
+use core::marker;
+use super::io::{
+    BinarySerializerDelegate, BinaryDeserializerDelegate,
+    forward_serializer_delegate, forward_deserializer_delegate,
+};
+
+/// Tags of the primitives written by `PrimitiveTagDelegate`.
+pub(crate) mod tag {
+    pub const BOOL: u8 = 0x01;
+    pub const I8: u8 = 0x02;
+    pub const I16: u8 = 0x03;
+    pub const I32: u8 = 0x04;
+    pub const I64: u8 = 0x05;
+    pub const U8: u8 = 0x06;
+    pub const U16: u8 = 0x07;
+    pub const U32: u8 = 0x08;
+    pub const U64: u8 = 0x09;
+    pub const F32: u8 = 0x0a;
+    pub const F64: u8 = 0x0b;
+    pub const CHAR: u8 = 0x0c;
+    pub const STR: u8 = 0x0d;
+    pub const BYTES: u8 = 0x0e;
+    pub const I128: u8 = 0x0f;
+    pub const U128: u8 = 0x10;
+}
+
+/// Wraps the delegate `H` and writes a one-byte tag before every primitive.
+///
+/// Booleans, integers, floats, chars, strings and byte arrays are tagged with their type,
+/// so `deserialize_any` can decode a scalar of unknown type, for example into a `Value`.
+/// Sequences, structs, enums and options are not tagged and still require the schema, so
+/// `#[serde(flatten)]` and untagged enums of anything but scalars do not work. It costs
+/// one byte per primitive.
+pub struct PrimitiveTagDelegate<H> {
+    phantom_data: marker::PhantomData<H>,
+}
+
+impl<H> BinarySerializerDelegate for PrimitiveTagDelegate<H>
+where
+    H: BinarySerializerDelegate,
+{
+    forward_serializer_delegate!(
+        H;
+        types,
+        encode_variant, encode_length, encode_sequence_length, encode_char,
+        length_prefixed_variants, encode_unit, interned_strings, option_bitmap, variant_tag,
+        write_integer,
+    );
+
+    fn primitive_tags() -> bool {
+        true
+    }
+}
+
+impl<H> BinaryDeserializerDelegate for PrimitiveTagDelegate<H>
+where
+    H: BinaryDeserializerDelegate,
+{
+    forward_deserializer_delegate!(
+        H;
+        types,
+        variant_size, length_size, sequence_length_size, char_size, decode_variant,
+        decode_length, decode_sequence_length, decode_char, length_prefixed_variants,
+        unit_size, decode_unit, interned_strings, option_bitmap, variant_index,
+        read_integer, read_signed_integer, read_variant, read_length, read_sequence_length,
+        read_char,
+    );
+
+    fn primitive_tags() -> bool {
+        true
+    }
+}
@@ -0,0 +1,247 @@
+use core::{cell::Cell, fmt};
+use serde::{
+    de::{Visitor, SeqAccess, MapAccess, DeserializeSeed, Deserialize},
+    Deserializer,
+};
+use super::io::Read;
+
+pub(crate) struct ProgressRead<'c, R> {
+    inner: R,
+    position: &'c Cell<usize>,
+}
+
+impl<'c, R> ProgressRead<'c, R> {
+    pub fn new(inner: R, position: &'c Cell<usize>) -> Self {
+        ProgressRead { inner, position }
+    }
+}
+
+impl<'c, 'de, R> Read<'de> for ProgressRead<'c, R>
+where
+    R: Read<'de>,
+{
+    type Error = R::Error;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'de [u8], Self::Error>> {
+        let position = self.position;
+        self.inner.read(length)
+            .map(|x| x.inspect(|_| position.set(position.get() + length)))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        let position = self.position;
+        self.inner.read_in_buffer(buffer, length)
+            .map(|()| position.set(position.get() + length))
+    }
+
+    fn is(&mut self) -> Option<()> {
+        self.inner.is()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+}
+
+// `deserializer` reads through a `ProgressRead` that counts into `position`
+pub(crate) fn deserialize_with_progress<'de, T, DD, F>(deserializer: DD, position: &Cell<usize>, callback: F) -> Result<T, DD::Error>
+where
+    T: Deserialize<'de>,
+    DD: Deserializer<'de>,
+    F: FnMut(usize),
+{
+    T::deserialize(Progress { deserializer, position, callback })
+}
+
+struct Progress<'c, T, F> {
+    deserializer: T,
+    position: &'c Cell<usize>,
+    callback: F,
+}
+
+macro_rules! forward {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.deserializer.$method(visitor)
+            }
+        )*
+    }
+}
+
+impl<'c, 'de, T, F> Deserializer<'de> for Progress<'c, T, F>
+where
+    T: Deserializer<'de>,
+    F: FnMut(usize),
+{
+    type Error = T::Error;
+
+    forward!(
+        deserialize_any, deserialize_bool,
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64, deserialize_i128,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64, deserialize_u128,
+        deserialize_f32, deserialize_f64, deserialize_char,
+        deserialize_str, deserialize_string, deserialize_bytes, deserialize_byte_buf,
+        deserialize_option, deserialize_unit, deserialize_identifier, deserialize_ignored_any
+    );
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.deserialize_seq(Progress { deserializer: visitor, position, callback })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.deserialize_tuple(len, Progress { deserializer: visitor, position, callback })
+    }
+
+    fn deserialize_tuple_struct<V>(self, name: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.deserialize_tuple_struct(name, len, Progress { deserializer: visitor, position, callback })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.deserialize_map(Progress { deserializer: visitor, position, callback })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.deserialize_struct(name, fields, Progress { deserializer: visitor, position, callback })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.is_human_readable()
+    }
+}
+
+impl<'c, 'de, V, F> Visitor<'de> for Progress<'c, V, F>
+where
+    V: Visitor<'de>,
+    F: FnMut(usize),
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.deserializer.expecting(f)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.visit_seq(Progress { deserializer: seq, position, callback })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let Progress { deserializer, position, callback } = self;
+        deserializer.visit_map(Progress { deserializer: map, position, callback })
+    }
+}
+
+impl<'c, 'de, A, F> SeqAccess<'de> for Progress<'c, A, F>
+where
+    A: SeqAccess<'de>,
+    F: FnMut(usize),
+{
+    type Error = A::Error;
+
+    fn next_element_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let element = self.deserializer.next_element_seed(seed)?;
+        if element.is_some() {
+            (self.callback)(self.position.get());
+        }
+        Ok(element)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.deserializer.size_hint()
+    }
+}
+
+impl<'c, 'de, A, F> MapAccess<'de> for Progress<'c, A, F>
+where
+    A: MapAccess<'de>,
+    F: FnMut(usize),
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.deserializer.next_key_seed(seed)
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let value = self.deserializer.next_value_seed(seed)?;
+        (self.callback)(self.position.get());
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.deserializer.size_hint()
+    }
+}
@@ -0,0 +1,120 @@
+use byteorder::ByteOrder;
+use either::Either;
+use serde::{Serialize, Serializer, ser::SerializeTuple};
+use super::{
+    io::{
+        Read,
+        BinarySerializerDelegate,
+        BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+    },
+    de::BinaryDeserializerError,
+    varint::{encode_varint, read_varint, max_value},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarintLength(usize);
+
+impl From<usize> for VarintLength {
+    fn from(v: usize) -> Self {
+        VarintLength(v)
+    }
+}
+
+impl Serialize for VarintLength {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (buffer, length) = encode_varint(self.0 as u64);
+        buffer[..length]
+            .iter()
+            .try_fold(serializer.serialize_tuple(length)?, |mut t, byte| {
+                t.serialize_element(byte).map(|()| t)
+            })
+            .and_then(SerializeTuple::end)
+    }
+}
+
+/// Frames strings and bytes like protobuf length-delimited scalars.
+///
+/// Every length, of a string, a byte array, a sequence or a map, is an unsigned LEB128
+/// varint followed by the raw content, so a `String` or a `Vec<u8>` matches the payload of
+/// a protobuf `string` or `bytes` field. This is only the scalar framing: no field tags or
+/// wire types are written, and integers, enum tags and chars keep the fixed width encoding.
+pub struct ProtobufLengthDelegate;
+
+impl BinarySerializerDelegate for ProtobufLengthDelegate {
+    type Variant = u32;
+    type Length = VarintLength;
+    type SequenceLength = VarintLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        VarintLength(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        VarintLength(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+impl BinaryDeserializerDelegate for ProtobufLengthDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        Some(DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes))
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_varint(read, max_value(Self::length_size())).map(|x| x as usize)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_length::<_, E>(read).map(Some)
+    }
+}
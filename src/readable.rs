@@ -0,0 +1,92 @@
+use core::{fmt, marker};
+use serde::{
+    de::{Visitor, Deserialize},
+    Serialize, Serializer, Deserializer,
+};
+
+pub(crate) const HUMAN_READABLE: &str = "$tirse::HumanReadable";
+pub(crate) const COMPACT: &str = "$tirse::Compact";
+
+/// Serializes the wrapped value as if the format were human-readable.
+///
+/// Types like `Ipv4Addr` choose their representation by `is_human_readable`, so a single
+/// payload can mix textual and binary fields. Other serializers see a newtype struct.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanReadable<T>(pub T);
+
+/// Serializes the wrapped value in the compact binary form, it undoes `HumanReadable`
+/// for a subtree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Compact<T>(pub T);
+
+impl<T> Serialize for HumanReadable<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(HUMAN_READABLE, &self.0)
+    }
+}
+
+impl<T> Serialize for Compact<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(COMPACT, &self.0)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for HumanReadable<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_newtype_struct(HUMAN_READABLE, Wrapped::<T>(marker::PhantomData))
+            .map(HumanReadable)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Compact<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_newtype_struct(COMPACT, Wrapped::<T>(marker::PhantomData))
+            .map(Compact)
+    }
+}
+
+struct Wrapped<T>(marker::PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for Wrapped<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a wrapped value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
@@ -1,4 +1,4 @@
-use core::{fmt, marker};
+use core::{fmt, marker, slice};
 use serde::{
     Serialize,
     Serializer,
@@ -17,25 +17,60 @@ use either::Either;
 use super::{
     io::{
         Write,
-        BinarySerializerDelegate
+        OffsetWrite,
+        CountingWrite,
+        IoError,
+        END_MARKER,
+        BinarySerializerDelegate,
+        DefaultBinarySerializerDelegate
     },
     err::{
         ErrorAdapter,
         DisplayCollector
-    }
+    },
+    endian::DefaultEndian,
+    readable::{
+        HUMAN_READABLE,
+        COMPACT
+    },
+    primitive::tag,
+    interned::WriteTable
 };
+#[cfg(feature = "use_std")]
+use super::io::{VecWrite, LimitWrite, PatchWrite, WriteWrapper};
 
-#[derive(Debug)]
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum BinarySerializerError {
-    // looks like there is nothing
+    #[cfg(not(feature = "use_std"))]
+    RequiredAlloc,
+    DepthLimitExceeded,
+    UnknownLength,
+    SizeLimitExceeded,
+    UnknownMark,
+    PreviousFieldFailed,
+    PatchInterned,
 }
 
 impl fmt::Display for BinarySerializerError {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
-        unreachable!()
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(not(feature = "use_std"))]
+            BinarySerializerError::RequiredAlloc => write!(f, "required alloc"),
+            BinarySerializerError::DepthLimitExceeded => write!(f, "depth limit exceeded"),
+            BinarySerializerError::UnknownLength => write!(f, "the length of a sequence must be known"),
+            BinarySerializerError::SizeLimitExceeded => write!(f, "size limit exceeded"),
+            BinarySerializerError::UnknownMark => write!(f, "no position is marked with this name"),
+            BinarySerializerError::PreviousFieldFailed => write!(f, "a previous field of the struct failed"),
+            BinarySerializerError::PatchInterned => write!(f, "cannot patch while strings are interned"),
+        }
     }
 }
 
+#[cfg(feature = "use_std")]
+impl std::error::Error for BinarySerializerError {}
+
 pub struct BinarySerializer<W, E, H, D>
 where
     W: Write,
@@ -44,6 +79,13 @@ where
     D: DisplayCollector,
 {
     write: W,
+    depth: usize,
+    max_depth: usize,
+    human_readable: bool,
+    length_prefixed_sequences: bool,
+    untagged: bool,
+    strings: Option<WriteTable>,
+    presence: Option<bool>,
     phantom_data: marker::PhantomData<(E, H, D)>,
 }
 
@@ -57,13 +99,425 @@ where
     pub fn new<WW: Into<W>>(write: WW) -> Self {
         BinarySerializer {
             write: write.into(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            human_readable: false,
+            length_prefixed_sequences: true,
+            untagged: false,
+            strings: H::interned_strings().then(WriteTable::default),
+            presence: None,
             phantom_data: marker::PhantomData,
         }
     }
 
+    /// Limits the nesting of sequences, maps, structs, options and enum variants with data,
+    /// 128 by default.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        BinarySerializer { max_depth, ..self }
+    }
+
+    /// Whether a sequence or a map starts with its length, true by default.
+    ///
+    /// Without the length the reader takes elements until the end of the input, so only
+    /// the last sequence or map of the input can be read back, and the deserializer must be
+    /// configured the same way.
+    pub fn length_prefixed_sequences(self, length_prefixed_sequences: bool) -> Self {
+        BinarySerializer { length_prefixed_sequences, ..self }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.write
+    }
+
+    /// The writer, to write something around the serialized values, like a header.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.write
+    }
+
     pub fn consume(self) -> W {
         self.write
     }
+
+    /// Flushes the writer and returns it, so a buffering writer holds nothing back.
+    pub fn finish(self) -> Result<W, SerializerError<W, D>> {
+        let mut write = self.write;
+        write
+            .flush()
+            .map(|()| write)
+            .map_err(Either::Right)
+            .map_err(ErrorAdapter::Inner)
+    }
+
+    fn split(&mut self) -> BinarySerializer<&mut W, E, H, D> {
+        BinarySerializer {
+            write: &mut self.write,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            human_readable: self.human_readable,
+            length_prefixed_sequences: self.length_prefixed_sequences,
+            untagged: false,
+            strings: self.strings.clone(),
+            presence: None,
+            phantom_data: marker::PhantomData,
+        }
+    }
+
+    /// Writes `bytes` as they are, without a length or any other framing.
+    ///
+    /// The deserializer cannot tell these bytes from the encoding around them, so the
+    /// reader must expect them at this very position, for example a magic header.
+    ///
+    /// `Serialize::serialize` is generic over the serializer and cannot call this method,
+    /// the caller writes the bytes before handing the serializer to the `Serialize` code.
+    /// Inside `Serialize` a fixed-size array of bytes in a tuple gives the same output as
+    /// long as the primitives are not tagged, see `PrimitiveTagDelegate`.
+    pub fn raw_write(&mut self, bytes: &[u8]) -> Result<(), SerializerError<W, D>> {
+        self.write
+            .write(bytes)
+            .map_err(Either::Right)
+            .map_err(ErrorAdapter::Inner)
+    }
+
+    #[cfg(feature = "use_std")]
+    fn buffer(&self) -> BinarySerializer<VecWrite, E, H, D> {
+        BinarySerializer {
+            write: VecWrite::default(),
+            depth: self.depth,
+            max_depth: self.max_depth,
+            human_readable: self.human_readable,
+            length_prefixed_sequences: self.length_prefixed_sequences,
+            untagged: false,
+            strings: self.strings.clone(),
+            presence: None,
+            phantom_data: marker::PhantomData,
+        }
+    }
+}
+
+impl<W, E, H, D> BinarySerializer<OffsetWrite<W>, E, H, D>
+where
+    W: Write,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: DisplayCollector,
+{
+    pub fn position(&self) -> usize {
+        self.write.position()
+    }
+
+    /// The number of bytes the serializer has written, to checkpoint positions.
+    pub fn bytes_written(&self) -> usize {
+        self.write.bytes_written()
+    }
+}
+
+impl<E, H, D> BinarySerializer<CountingWrite, E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: DisplayCollector,
+{
+    pub fn bytes_written(&self) -> usize {
+        self.write.count()
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<E, H, D> BinarySerializer<WriteWrapper<Vec<u8>>, E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: DisplayCollector,
+{
+    /// Empties the buffer for the next message, keeping its capacity.
+    ///
+    /// In a loop serializing many small messages, serialize into the same serializer,
+    /// take the bytes from `get_ref`, then `reset`, so the buffer is allocated once.
+    pub fn reset(&mut self) {
+        self.write.get_mut().clear();
+        self.depth = 0;
+        self.strings = H::interned_strings().then(WriteTable::default);
+    }
+}
+
+#[cfg(feature = "use_std")]
+impl<E, H, D> BinarySerializer<PatchWrite, E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    pub fn position(&self) -> usize {
+        self.write.position()
+    }
+
+    /// Remembers the current position as `name`, for example before a placeholder.
+    pub fn mark(&mut self, name: &str) {
+        self.write.mark(name)
+    }
+
+    /// Overwrites the bytes at the position marked `name` with the encoding of `value`.
+    ///
+    /// The encoding must fit in the bytes already written after the mark, so the
+    /// placeholder should have the same encoded size as the final value.
+    ///
+    /// Fails with `InternedStringDelegate`, the reader numbers the strings in the order
+    /// they are written, which a patch cannot follow.
+    pub fn patch<T>(&mut self, name: &str, value: &T) -> Result<(), SerializerError<PatchWrite, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.strings.is_some() {
+            return Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PatchInterned)));
+        }
+        let offset = self.write
+            .offset(name)
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownMark)))?;
+        value
+            .serialize(self.buffer())
+            .map_err(from_buffer_error::<PatchWrite, D>)
+            .and_then(|buffer| self.write
+                .patch_at(offset, &buffer.consume().0)
+                .map_err(Either::Right)
+                .map_err(ErrorAdapter::Inner)
+            )
+    }
+}
+
+impl<W, E, H, D> BinarySerializer<W, E, H, D>
+where
+    W: Write,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    fn enter(self) -> Result<Self, SerializerError<W, D>> {
+        if self.depth >= self.max_depth {
+            Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::DepthLimitExceeded)))
+        } else {
+            Ok(BinarySerializer { depth: self.depth + 1, presence: None, ..self })
+        }
+    }
+
+    fn leave(self) -> Self {
+        BinarySerializer { depth: self.depth - 1, ..self }
+    }
+
+    // with `PrimitiveTagDelegate` writes the tag of a primitive, the primitive itself is
+    // written by `f` without nested tags
+    fn primitive<F>(self, tag: u8, f: F) -> Result<Self, SerializerError<W, D>>
+    where
+        F: FnOnce(Self) -> Result<Self, SerializerError<W, D>>,
+    {
+        if !H::primitive_tags() || self.untagged {
+            return f(self);
+        }
+        let mut s = self;
+        s.raw_write(&[tag])?;
+        s.untagged(f)
+    }
+
+    // lengths and enum tags are never tagged as primitives
+    fn untagged<F>(self, f: F) -> Result<Self, SerializerError<W, D>>
+    where
+        F: FnOnce(Self) -> Result<Self, SerializerError<W, D>>,
+    {
+        let outer = self.untagged;
+        f(BinarySerializer { untagged: true, ..self })
+            .map(|s| BinarySerializer { untagged: outer, ..s })
+    }
+
+    // with `InternedStringDelegate` writes zero and the string itself the first time,
+    // otherwise the index of the string plus one
+    #[cfg(feature = "use_std")]
+    fn interned(self, v: &str) -> Result<Self, SerializerError<W, D>> {
+        match self.strings.as_ref().map(|strings| strings.intern(v)) {
+            None => self.serialize_bytes(v.as_bytes()),
+            Some(Some(index)) => self.untagged(|s| H::encode_variant(index + 1).serialize(s)),
+            Some(None) => self
+                .untagged(|s| H::encode_variant(0).serialize(s))
+                .and_then(|s| s.serialize_bytes(v.as_bytes())),
+        }
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn interned(self, v: &str) -> Result<Self, SerializerError<W, D>> {
+        match self.strings {
+            None => self.serialize_bytes(v.as_bytes()),
+            Some(_) => Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::RequiredAlloc))),
+        }
+    }
+
+    /// Writes every element of `items` without the count, the counterpart of
+    /// `BinaryDeserializer::read_exact_elements`.
+    pub fn write_elements<T>(&mut self, items: &[T]) -> Result<(), SerializerError<W, D>>
+    where
+        T: Serialize,
+    {
+        items
+            .iter()
+            .try_for_each(|item| item.serialize(self.split()).map(|_| ()))
+    }
+
+    /// Writes the length prefix of the delegate followed by the raw `bytes`, it reads back
+    /// with `deserialize_bytes`.
+    pub fn serialize_length_prefixed_bytes(self, bytes: &[u8]) -> Result<Self, SerializerError<W, D>> {
+        self.untagged(|s| H::encode_length(bytes.len()).serialize(s))
+            .and_then(|mut s| s.raw_write(bytes).map(|()| s))
+    }
+
+    /// Writes `value` followed by `END_MARKER`, so the reader detects a truncated stream
+    /// even if the value itself decodes. It costs four bytes per value.
+    pub fn serialize_with_end_marker<T>(self, value: &T) -> Result<Self, SerializerError<W, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self).and_then(|mut s| s.raw_write(&END_MARKER).map(|()| s))
+    }
+
+    /// Writes `value` and returns the bytes of its encoding as well, for example to
+    /// compute a checksum that follows it.
+    #[cfg(feature = "use_std")]
+    pub fn serialize_captured<T>(self, value: &T) -> Result<(Self, Vec<u8>), SerializerError<W, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let buffer = self.buffer();
+        value
+            .serialize(buffer)
+            .map_err(from_buffer_error::<W, D>)
+            .map(|buffer| buffer.consume().0)
+            .and_then(|bytes| {
+                let mut s = self;
+                s.raw_write(&bytes).map(|()| (s, bytes))
+            })
+    }
+
+    /// Writes the length of the encoded `value` followed by the encoding itself.
+    ///
+    /// The value is encoded into a temporary buffer first, so the reader can skip it
+    /// without knowing its type.
+    #[cfg(feature = "use_std")]
+    pub fn serialize_framed<T>(self, value: &T) -> Result<Self, SerializerError<W, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_payload(value)
+    }
+
+    #[cfg(feature = "use_std")]
+    fn serialize_payload<T>(self, value: &T) -> Result<Self, SerializerError<W, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let buffer = self.buffer();
+        value
+            .serialize(buffer)
+            .map_err(from_buffer_error::<W, D>)
+            .and_then(|buffer| self.serialize_length_prefixed_bytes(&buffer.consume().0))
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn serialize_payload<T>(self, value: &T) -> Result<Self, SerializerError<W, D>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _ = value;
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::RequiredAlloc)))
+    }
+}
+
+type SerializerError<W, D> = ErrorAdapter<Either<BinarySerializerError, <W as Write>::Error>, D>;
+
+/// Serializes `value` into the beginning of `buf` with the default encoding and zero-fills
+/// the rest of it, returns the length of the encoding.
+///
+/// Fails if the encoding does not fit in `buf`, the content of `buf` is unspecified then.
+pub fn to_slice_padded<T, D>(buf: &mut [u8], value: &T) -> Result<usize, ErrorAdapter<Either<BinarySerializerError, IoError>, D>>
+where
+    T: ?Sized + Serialize,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    let size = buf.len();
+    value
+        .serialize(BinarySerializer::<slice::IterMut<u8>, DefaultEndian, DefaultBinarySerializerDelegate, D>::new(buf.iter_mut()))
+        .map(|s| {
+            let rest = s.consume().into_slice();
+            rest.iter_mut().for_each(|x| *x = 0);
+            size - rest.len()
+        })
+}
+
+/// The length of the default encoding of `value`, without writing it anywhere.
+pub fn serialized_size<T, D>(value: &T) -> Result<usize, ErrorAdapter<BinarySerializerError, D>>
+where
+    T: ?Sized + Serialize,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    value
+        .serialize(BinarySerializer::<CountingWrite, DefaultEndian, DefaultBinarySerializerDelegate, D>::new(CountingWrite::default()))
+        .map(|s| s.consume().count())
+        .map_err(|e| match e {
+            ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(e),
+            ErrorAdapter::Inner(Either::Right(e)) => match e {},
+            ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+        })
+}
+
+/// The error of `to_vec`, writing to memory cannot fail, so there is no error of the writer.
+#[cfg(feature = "use_std")]
+pub type ToVecError = ErrorAdapter<BinarySerializerError, String>;
+
+/// Serializes `value` with the default encoding into a new vector.
+#[cfg(feature = "use_std")]
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, ToVecError>
+where
+    T: ?Sized + Serialize,
+{
+    value
+        .serialize(BinarySerializer::<VecWrite, DefaultEndian, DefaultBinarySerializerDelegate, String>::new(VecWrite::default()))
+        .map(|s| s.consume().0)
+        .map_err(|e| match e {
+            ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(e),
+            ErrorAdapter::Inner(Either::Right(e)) => match e {},
+            ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+        })
+}
+
+/// Serializes `value` with the default encoding into a new vector of at most `max` bytes.
+///
+/// Fails with `SizeLimitExceeded` as soon as the encoding outgrows `max`, the rest of the
+/// value is not serialized then.
+#[cfg(feature = "use_std")]
+pub fn to_vec_limited<T, D>(max: usize, value: &T) -> Result<Vec<u8>, ErrorAdapter<BinarySerializerError, D>>
+where
+    T: ?Sized + Serialize,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    value
+        .serialize(BinarySerializer::<LimitWrite<VecWrite>, DefaultEndian, DefaultBinarySerializerDelegate, D>::new(LimitWrite::new(VecWrite::default(), max)))
+        .map(|s| s.consume().into_inner().0)
+        .map_err(|e| match e {
+            ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(e),
+            ErrorAdapter::Inner(Either::Right(Either::Left(IoError { .. }))) => {
+                ErrorAdapter::Inner(BinarySerializerError::SizeLimitExceeded)
+            },
+            ErrorAdapter::Inner(Either::Right(Either::Right(e))) => match e {},
+            ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+        })
+}
+
+#[cfg(feature = "use_std")]
+fn from_buffer_error<W, D>(e: SerializerError<VecWrite, D>) -> SerializerError<W, D>
+where
+    W: Write,
+    D: DisplayCollector,
+{
+    match e {
+        ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Inner(Either::Left(e)),
+        ErrorAdapter::Inner(Either::Right(e)) => match e {},
+        ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+    }
 }
 
 impl<W, E, H, D> Serializer for BinarySerializer<W, E, H, D>
@@ -84,143 +538,139 @@ where
     type SerializeStructVariant = BinarySerializeStructVariant<W, E, H, D>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.serialize_i8(if v { 1 } else { 0 })
+        self.primitive(tag::BOOL, |s| s.serialize_i8(if v { 1 } else { 0 }))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u8(v as _)
+        self.primitive(tag::I8, |s| s.serialize_u8(v as _))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u16(v as _)
+        self.primitive(tag::I16, |s| s.serialize_u16(v as _))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u32(v as _)
+        self.primitive(tag::I32, |s| s.serialize_u32(v as _))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v as _)
+        self.primitive(tag::I64, |s| s.serialize_u64(v as _))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.primitive(tag::I128, |s| s.serialize_u128(v as _))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        let mut mut_self = self;
-        mut_self
-            .write
-            .write(&[v])
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::U8, |mut s| s.raw_write(&[v]).map(|()| s))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         use core::mem;
 
-        let mut mut_self = self;
-        let mut buffer = [0; mem::size_of::<u16>()];
-        E::write_u16(&mut buffer, v);
-        mut_self
-            .write
-            .write(&buffer)
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::U16, |mut s| {
+            H::write_integer::<_, E>(&mut s.write, v.into(), mem::size_of::<u16>())
+                .map_err(Either::Right)
+                .map_err(ErrorAdapter::Inner)
+                .map(|_| s)
+        })
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
         use core::mem;
 
-        let mut mut_self = self;
-        let mut buffer = [0; mem::size_of::<u32>()];
-        E::write_u32(&mut buffer, v);
-        mut_self
-            .write
-            .write(&buffer)
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::U32, |mut s| {
+            H::write_integer::<_, E>(&mut s.write, v.into(), mem::size_of::<u32>())
+                .map_err(Either::Right)
+                .map_err(ErrorAdapter::Inner)
+                .map(|_| s)
+        })
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
         use core::mem;
 
-        let mut mut_self = self;
-        let mut buffer = [0; mem::size_of::<u64>()];
-        E::write_u64(&mut buffer, v);
-        mut_self
-            .write
-            .write(&buffer)
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::U64, |mut s| {
+            H::write_integer::<_, E>(&mut s.write, v, mem::size_of::<u64>())
+                .map_err(Either::Right)
+                .map_err(ErrorAdapter::Inner)
+                .map(|_| s)
+        })
+    }
+
+    // 128-bit integers are always written in full, the delegate encodes up to 64 bits
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        use core::mem;
+
+        let mut buffer = [0; mem::size_of::<u128>()];
+        E::write_u128(&mut buffer, v);
+        self.primitive(tag::U128, |mut s| s.raw_write(&buffer).map(|()| s))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         use core::mem;
 
-        let mut mut_self = self;
         let mut buffer = [0; mem::size_of::<f32>()];
         E::write_f32(&mut buffer, v);
-        mut_self
-            .write
-            .write(&buffer)
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::F32, |mut s| s.raw_write(&buffer).map(|()| s))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
         use core::mem;
 
-        let mut mut_self = self;
         let mut buffer = [0; mem::size_of::<f64>()];
         E::write_f64(&mut buffer, v);
-        mut_self
-            .write
-            .write(&buffer)
-            .map_err(Either::Right)
-            .map_err(ErrorAdapter::Inner)
-            .map(|_| mut_self)
+        self.primitive(tag::F64, |mut s| s.raw_write(&buffer).map(|()| s))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        H::encode_char(v).serialize(self)
+        self.primitive(tag::CHAR, |s| H::encode_char(v).serialize(s))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.serialize_bytes(v.as_bytes())
+        self.primitive(tag::STR, |s| s.interned(v))
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let seq = self.serialize_seq(Some(v.len()));
-        (0..v.len())
-            .fold(seq, |seq, index| {
-                seq.and_then(|mut s| s.serialize_element(&v[index]).map(|_| s))
-            })
-            .and_then(|s| s.end())
+        self.primitive(tag::BYTES, |s| s.serialize_length_prefixed_bytes(v))
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        H::encode_variant(0)
-            .serialize(self)
+        if self.presence.is_some() {
+            return Ok(self);
+        }
+        self.untagged(|s| H::encode_variant(0).serialize(s))
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        H::encode_variant(1)
-            .serialize(self)
+        if self.presence.is_some() {
+            return self.enter()
+                .and_then(|s| value.serialize(s))
+                .map(Self::leave)
+                .map(|s| BinarySerializer { presence: Some(true), ..s });
+        }
+        self.untagged(|s| H::encode_variant(1).serialize(s))
+            .and_then(Self::enter)
             .and_then(|s| value.serialize(s))
+            .map(Self::leave)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self)
+        let mut mut_self = self;
+        mut_self
+            .write
+            .write(H::encode_unit())
+            .map_err(Either::Right)
+            .map_err(ErrorAdapter::Inner)
+            .map(|_| mut_self)
     }
 
     fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
         let _ = name;
-        Ok(self)
+        self.serialize_unit()
     }
 
     fn serialize_unit_variant(
@@ -230,23 +680,35 @@ where
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
         let _ = name;
-        let _ = variant;
-        H::encode_variant(variant_index).serialize(self)
+        self.untagged(|s| H::encode_variant(H::variant_tag(variant_index, variant)).serialize(s))
+            .and_then(|s| if H::length_prefixed_variants() {
+                s.serialize_length_prefixed_bytes(&[])
+            } else {
+                Ok(s)
+            })
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        let _ = name;
-        value.serialize(self)
+        let human_readable = match name {
+            HUMAN_READABLE => true,
+            COMPACT => false,
+            _ => self.human_readable,
+        };
+        // a newtype is transparent and takes no level, as for the deserializer
+        let outer = self.human_readable;
+        value
+            .serialize(BinarySerializer { human_readable, presence: None, ..self })
+            .map(|s| BinarySerializer { human_readable: outer, ..s })
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
         variant_index: u32,
@@ -254,27 +716,35 @@ where
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let _ = name;
-        let _ = variant;
-        H::encode_variant(variant_index)
-            .serialize(self)
-            .and_then(|s| value.serialize(s))
+        self.untagged(|s| H::encode_variant(H::variant_tag(variant_index, variant)).serialize(s))
+            .and_then(Self::enter)
+            .and_then(|s| if H::length_prefixed_variants() {
+                s.serialize_payload(value)
+            } else {
+                value.serialize(s)
+            })
+            .map(Self::leave)
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let maybe_self = match len {
-            Some(len) => H::encode_sequence_length(len).serialize(self),
-            None => Ok(self),
+            _ if !self.length_prefixed_sequences => Ok(self),
+            Some(len) => self.untagged(|s| H::encode_sequence_length(len).serialize(s)),
+            None => Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownLength))),
         };
-        maybe_self.and_then(|x| Ok(BinarySerializeSeq { raw: Ok(x) }))
+        maybe_self
+            .and_then(Self::enter)
+            .map(|x| BinarySerializeSeq { raw: Ok(x) })
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         let _ = len;
-        let sequence = BinarySerializeSeq { raw: Ok(self) };
-        Ok(BinarySerializeTuple { sequence: sequence })
+        self.enter()
+            .map(|s| BinarySerializeSeq { raw: Ok(s) })
+            .map(|sequence| BinarySerializeTuple { sequence })
     }
 
     fn serialize_tuple_struct(
@@ -284,8 +754,9 @@ where
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         let _ = name;
         let _ = len;
-        let sequence = BinarySerializeSeq { raw: Ok(self) };
-        Ok(BinarySerializeTupleStruct { sequence: sequence })
+        self.enter()
+            .map(|s| BinarySerializeSeq { raw: Ok(s) })
+            .map(|sequence| BinarySerializeTupleStruct { sequence })
     }
 
     fn serialize_tuple_variant(
@@ -296,25 +767,25 @@ where
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         let _ = name;
-        let _ = variant;
         let _ = len;
-        H::encode_variant(variant_index)
-            .serialize(self)
-            .and_then(|x| {
-                let sequence = BinarySerializeSeq { raw: Ok(x) };
-                Ok(BinarySerializeTupleVariant { sequence: sequence })
-            })
+        self.untagged(|s| H::encode_variant(H::variant_tag(variant_index, variant)).serialize(s))
+            .and_then(Self::enter)
+            .and_then(VariantPayload::new)
+            .map(|payload| BinarySerializeTupleVariant { payload })
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         let maybe_self = match len {
-            Some(len) => H::encode_length(len).serialize(self),
-            None => Ok(self),
+            _ if !self.length_prefixed_sequences => Ok(self),
+            Some(len) => self.untagged(|s| H::encode_sequence_length(len).serialize(s)),
+            None => Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownLength))),
         };
-        maybe_self.and_then(|x| {
-            let sequence = BinarySerializeSeq { raw: Ok(x) };
-            Ok(BinarySerializeMap { sequence: sequence })
-        })
+        maybe_self
+            .and_then(Self::enter)
+            .map(|x| {
+                let sequence = BinarySerializeSeq { raw: Ok(x) };
+                BinarySerializeMap { sequence }
+            })
     }
 
     fn serialize_struct(
@@ -323,9 +794,8 @@ where
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         let _ = name;
-        let _ = len;
-        let sequence = BinarySerializeSeq { raw: Ok(self) };
-        Ok(BinarySerializeStruct { sequence: sequence })
+        self.enter()
+            .and_then(|s| BinarySerializeStruct::new(s, len))
     }
 
     fn serialize_struct_variant(
@@ -336,14 +806,11 @@ where
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         let _ = name;
-        let _ = variant;
         let _ = len;
-        H::encode_variant(variant_index)
-            .serialize(self)
-            .and_then(|x| {
-                let sequence = BinarySerializeSeq { raw: Ok(x) };
-                Ok(BinarySerializeStructVariant { sequence: sequence })
-            })
+        self.untagged(|s| H::encode_variant(H::variant_tag(variant_index, variant)).serialize(s))
+            .and_then(Self::enter)
+            .and_then(VariantPayload::new)
+            .map(|payload| BinarySerializeStructVariant { payload })
     }
 
     #[cfg(not(feature = "use_std"))]
@@ -354,11 +821,28 @@ where
         D::display(value).serialize(self)
     }
 
+    // formats into one `String` and writes it like `serialize_str`, a failing `Display`
+    // is an error instead of the panic of `ToString`
+    #[cfg(feature = "use_std")]
+    fn collect_str<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + fmt::Display,
+    {
+        use core::fmt::Write as _;
+
+        let mut buffer = String::new();
+        write!(buffer, "{}", value)
+            .map_err(|_| serde::ser::Error::custom("a Display implementation returned an error"))
+            .and_then(|()| self.serialize_str(&buffer))
+    }
+
     fn is_human_readable(&self) -> bool {
-        false
+        self.human_readable
     }
 }
 
+type SequenceState<W, E, H, D> = Result<BinarySerializer<W, E, H, D>, Option<SerializerError<W, D>>>;
+
 pub struct BinarySerializeSeq<W, E, H, D>
 where
     W: Write,
@@ -366,7 +850,7 @@ where
     H: BinarySerializerDelegate,
     D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
 {
-    raw: Result<BinarySerializer<W, E, H, D>, Option<ErrorAdapter<Either<BinarySerializerError, W::Error>, D>>>,
+    raw: SequenceState<W, E, H, D>,
 }
 
 impl<W, E, H, D> SerializeSeq for BinarySerializeSeq<W, E, H, D>
@@ -397,7 +881,7 @@ where
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.raw.map_err(Option::unwrap)
+        self.raw.map(BinarySerializer::leave).map_err(Option::unwrap)
     }
 }
 
@@ -472,7 +956,7 @@ where
     H: BinarySerializerDelegate,
     D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
 {
-    sequence: BinarySerializeSeq<W, E, H, D>,
+    payload: VariantPayload<W, E, H, D>,
 }
 
 impl<W, E, H, D> SerializeTupleVariant for BinarySerializeTupleVariant<W, E, H, D>
@@ -489,11 +973,11 @@ where
     where
         T: Serialize + ?Sized,
     {
-        self.sequence.serialize_element(value)
+        self.payload.serialize_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.sequence.end()
+        self.payload.end()
     }
 }
 
@@ -544,6 +1028,41 @@ where
     D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
 {
     sequence: BinarySerializeSeq<W, E, H, D>,
+    #[cfg(feature = "use_std")]
+    bitmap: Option<OptionBitmap<E, H, D>>,
+}
+
+impl<W, E, H, D> BinarySerializeStruct<W, E, H, D>
+where
+    W: Write,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    #[cfg(feature = "use_std")]
+    fn new(serializer: BinarySerializer<W, E, H, D>, len: usize) -> Result<Self, SerializerError<W, D>> {
+        let bitmap = if H::option_bitmap() {
+            Some(OptionBitmap::new(serializer.buffer(), len))
+        } else {
+            None
+        };
+        Ok(BinarySerializeStruct {
+            sequence: BinarySerializeSeq { raw: Ok(serializer) },
+            bitmap,
+        })
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn new(serializer: BinarySerializer<W, E, H, D>, len: usize) -> Result<Self, SerializerError<W, D>> {
+        let _ = len;
+        if H::option_bitmap() {
+            Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::RequiredAlloc)))
+        } else {
+            Ok(BinarySerializeStruct {
+                sequence: BinarySerializeSeq { raw: Ok(serializer) },
+            })
+        }
+    }
 }
 
 impl<W, E, H, D> SerializeStruct for BinarySerializeStruct<W, E, H, D>
@@ -562,14 +1081,89 @@ where
     {
         let _ = key;
 
+        #[cfg(feature = "use_std")]
+        {
+            if let Some(ref mut bitmap) = self.bitmap {
+                return bitmap.serialize_field(value).map_err(from_buffer_error::<W, D>);
+            }
+        }
         self.sequence.serialize_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "use_std")]
+        {
+            if let Some(bitmap) = self.bitmap {
+                return self.sequence.end().and_then(|s| bitmap.end(s));
+            }
+        }
         self.sequence.end()
     }
 }
 
+// with `OptionBitmapDelegate` the fields of a struct are buffered, so the bitmap of
+// the present options is written before them
+#[cfg(feature = "use_std")]
+struct OptionBitmap<E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    buffer: Option<BinarySerializer<VecWrite, E, H, D>>,
+    bitmap: Vec<u8>,
+    index: usize,
+}
+
+#[cfg(feature = "use_std")]
+impl<E, H, D> OptionBitmap<E, H, D>
+where
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    fn new(buffer: BinarySerializer<VecWrite, E, H, D>, len: usize) -> Self {
+        OptionBitmap {
+            buffer: Some(buffer),
+            bitmap: vec![0; len.div_ceil(8)],
+            index: 0,
+        }
+    }
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializerError<VecWrite, D>>
+    where
+        T: Serialize + ?Sized,
+    {
+        let index = self.index;
+        self.index += 1;
+        let buffer = self.buffer.take()
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PreviousFieldFailed)))?;
+        let buffer = value.serialize(BinarySerializer { presence: Some(false), ..buffer })?;
+        if buffer.presence == Some(true) {
+            if index / 8 >= self.bitmap.len() {
+                self.bitmap.resize(index / 8 + 1, 0);
+            }
+            self.bitmap[index / 8] |= 1 << (index % 8);
+        }
+        self.buffer = Some(BinarySerializer { presence: None, ..buffer });
+        Ok(())
+    }
+
+    fn end<W>(self, serializer: BinarySerializer<W, E, H, D>) -> Result<BinarySerializer<W, E, H, D>, SerializerError<W, D>>
+    where
+        W: Write,
+    {
+        let fields = self.buffer
+            .ok_or(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PreviousFieldFailed)))?
+            .consume()
+            .0;
+        let mut s = serializer;
+        s.raw_write(&self.bitmap)
+            .and_then(|()| s.raw_write(&fields))
+            .map(|()| s)
+    }
+}
+
 pub struct BinarySerializeStructVariant<W, E, H, D>
 where
     W: Write,
@@ -577,7 +1171,7 @@ where
     H: BinarySerializerDelegate,
     D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
 {
-    sequence: BinarySerializeSeq<W, E, H, D>,
+    payload: VariantPayload<W, E, H, D>,
 }
 
 impl<W, E, H, D> SerializeStructVariant for BinarySerializeStructVariant<W, E, H, D>
@@ -596,10 +1190,86 @@ where
     {
         let _ = key;
 
-        self.sequence.serialize_element(value)
+        self.payload.serialize_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.payload.end()
+    }
+}
+
+struct VariantPayload<W, E, H, D>
+where
+    W: Write,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    sequence: BinarySerializeSeq<W, E, H, D>,
+    #[cfg(feature = "use_std")]
+    buffer: Option<BinarySerializeSeq<VecWrite, E, H, D>>,
+}
+
+impl<W, E, H, D> VariantPayload<W, E, H, D>
+where
+    W: Write,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+    D: Serialize + DisplayCollector + fmt::Display + fmt::Debug,
+{
+    #[cfg(feature = "use_std")]
+    fn new(serializer: BinarySerializer<W, E, H, D>) -> Result<Self, SerializerError<W, D>> {
+        let buffer = if H::length_prefixed_variants() {
+            let raw = serializer.buffer();
+            Some(BinarySerializeSeq { raw: Ok(raw) })
+        } else {
+            None
+        };
+        Ok(VariantPayload {
+            sequence: BinarySerializeSeq { raw: Ok(serializer) },
+            buffer,
+        })
+    }
+
+    #[cfg(not(feature = "use_std"))]
+    fn new(serializer: BinarySerializer<W, E, H, D>) -> Result<Self, SerializerError<W, D>> {
+        if H::length_prefixed_variants() {
+            Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::RequiredAlloc)))
+        } else {
+            Ok(VariantPayload {
+                sequence: BinarySerializeSeq { raw: Ok(serializer) },
+            })
+        }
+    }
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializerError<W, D>>
+    where
+        T: Serialize + ?Sized,
+    {
+        #[cfg(feature = "use_std")]
+        {
+            if let Some(ref mut buffer) = self.buffer {
+                return buffer.serialize_element(value).map_err(from_buffer_error::<W, D>);
+            }
+        }
+        self.sequence.serialize_element(value)
+    }
+
+    fn end(self) -> Result<BinarySerializer<W, E, H, D>, SerializerError<W, D>> {
+        #[cfg(feature = "use_std")]
+        {
+            if let Some(buffer) = self.buffer {
+                let sequence = self.sequence;
+                return buffer
+                    .end()
+                    .map_err(from_buffer_error::<W, D>)
+                    .and_then(|buffer| {
+                        sequence
+                            .end()
+                            .and_then(|s| s.serialize_length_prefixed_bytes(&buffer.consume().0))
+                    });
+            }
+        }
         self.sequence.end()
     }
 }
@@ -0,0 +1,86 @@
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use core::convert::Infallible;
+use byteorder::{ByteOrder, LittleEndian, BigEndian};
+use either::Either;
+use super::{
+    io::{
+        Read, IoError, VecWrite, BinarySerializerDelegate, BinaryDeserializerDelegate,
+        DefaultBinarySerializerDelegate, DefaultBinaryDeserializerDelegate,
+    },
+    ser::{BinarySerializer, BinarySerializerError},
+    de::{BinaryDeserializer, BinaryDeserializerError},
+    endian::Endianness,
+    err::ErrorAdapter,
+    value::Value,
+};
+
+pub type SwapError = ErrorAdapter<Either<BinaryDeserializerError, IoError>, String>;
+
+fn decode<T, E, H>(bytes: &[u8]) -> Result<T, SwapError>
+where
+    T: DeserializeOwned,
+    E: ByteOrder,
+    H: BinaryDeserializerDelegate,
+{
+    T::deserialize(BinaryDeserializer::<_, E, H, String>::new(bytes.iter()))
+}
+
+fn encode<T, E, H>(value: &T) -> Result<Vec<u8>, SwapError>
+where
+    T: Serialize,
+    E: ByteOrder,
+    H: BinarySerializerDelegate,
+{
+    value
+        .serialize(BinarySerializer::<VecWrite, E, H, String>::new(VecWrite::default()))
+        .map(|s| s.consume().0)
+        .map_err(from_serializer_error)
+}
+
+fn from_serializer_error(e: ErrorAdapter<Either<BinarySerializerError, Infallible>, String>) -> SwapError {
+    match e {
+        ErrorAdapter::Inner(Either::Left(e)) => ErrorAdapter::Outer(e.to_string()),
+        ErrorAdapter::Inner(Either::Right(e)) => match e {},
+        ErrorAdapter::Outer(d) => ErrorAdapter::Outer(d),
+    }
+}
+
+/// Decodes a `T` written with the `from` byte order and encodes it again with `to`.
+pub fn swap_endianness<T>(bytes: &[u8], from: Endianness, to: Endianness) -> Result<Vec<u8>, SwapError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    match from {
+        Endianness::Little => decode::<T, LittleEndian, DefaultBinaryDeserializerDelegate>(bytes),
+        Endianness::Big => decode::<T, BigEndian, DefaultBinaryDeserializerDelegate>(bytes),
+    }
+    .and_then(|value| match to {
+        Endianness::Little => encode::<T, LittleEndian, DefaultBinarySerializerDelegate>(&value),
+        Endianness::Big => encode::<T, BigEndian, DefaultBinarySerializerDelegate>(&value),
+    })
+}
+
+/// Decodes the primitives written with the byte order `E` and delegate `H`, and encodes
+/// them again with `F` and `G`, without knowing the type of the blob.
+///
+/// It requires the self-describing format, `H` must tag the primitives like
+/// `PrimitiveTagDelegate`, otherwise it fails with `NotSelfDescribing`. Every primitive is
+/// decoded into a `Value` by its tag until the end of the input. The lengths of sequences,
+/// the options and the enum variants are not tagged, so only a blob of primitives, for
+/// example a struct, a tuple or an array of them, can be transcoded. `G` may leave out the
+/// tags, the result is then read with the concrete type.
+pub fn transcode<E, H, F, G>(bytes: &[u8]) -> Result<Vec<u8>, SwapError>
+where
+    E: ByteOrder,
+    H: BinaryDeserializerDelegate,
+    F: ByteOrder,
+    G: BinarySerializerDelegate,
+{
+    let mut d = BinaryDeserializer::<_, E, H, String>::new(bytes.iter());
+    let mut s = BinarySerializer::<VecWrite, F, G, String>::new(VecWrite::default());
+    while d.get_mut().is().is_some() {
+        let value = Value::deserialize(d.split())?;
+        s = value.serialize(s).map_err(from_serializer_error)?;
+    }
+    Ok(s.consume().0)
+}
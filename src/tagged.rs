@@ -0,0 +1,159 @@
+use byteorder::ByteOrder;
+use either::Either;
+use super::{
+    io::{
+        Read,
+        Write,
+        BinarySerializerDelegate,
+        BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+        read_small,
+        sign_extend,
+    },
+    de::BinaryDeserializerError,
+};
+
+/// Writes the width of every integer wider than one byte in a tag byte before its value.
+///
+/// The deserializer accepts an integer narrower than the target type and extends it, with
+/// the sign for signed targets, so a field widened from `u32` to `u64` still reads the old
+/// data. Narrowing is an error. This only works when the data was written with this
+/// delegate, and single bytes (`u8`, `i8`) are never tagged so they cannot be widened.
+pub struct TaggedIntDelegate;
+
+fn read_tagged<'de, R, E>(read: &mut R, size: usize) -> Result<(u64, usize), Either<BinaryDeserializerError, R::Error>>
+where
+    R: Read<'de>,
+    E: ByteOrder,
+{
+    let width = read_small::<_, [u8; 1], _, _>(read, 1, |b| b[0])?;
+    if width == 0 || usize::from(width) > size.min(8) {
+        Err(Either::Left(BinaryDeserializerError::WrongIntegerWidth(width)))
+    } else {
+        let width = usize::from(width);
+        read_small::<_, [u8; 8], _, _>(read, width, |b| E::read_uint(b, width))
+            .map(|v| (v, width))
+    }
+}
+
+impl BinarySerializerDelegate for TaggedIntDelegate {
+    type Variant = u32;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        v
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        v
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+
+    fn write_integer<W, E>(write: &mut W, v: u64, size: usize) -> Result<(), W::Error>
+    where
+        W: Write,
+        E: ByteOrder,
+    {
+        let mut buffer = [0; 9];
+        buffer[0] = size as u8;
+        E::write_uint(&mut buffer[1..], v, size);
+        write.write(&buffer[..(size + 1)])
+    }
+}
+
+impl BinaryDeserializerDelegate for TaggedIntDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn read_integer<'de, R, E>(read: &mut R, size: usize) -> Result<u64, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_tagged::<_, E>(read, size).map(|(v, _)| v)
+    }
+
+    fn read_signed_integer<'de, R, E>(read: &mut R, size: usize) -> Result<i64, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_tagged::<_, E>(read, size).map(|(v, width)| sign_extend(v, width))
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::variant_size()).map(|x| x as u32)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::length_size()).map(|x| x as usize)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::sequence_length_size()).map(|x| Some(x as usize))
+    }
+
+    fn read_char<'de, R, E>(read: &mut R) -> Result<char, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::char_size()).and_then(|code| {
+            let code = code as u32;
+            core::char::from_u32(code)
+                .ok_or(Either::Left(BinaryDeserializerError::WrongChar(code)))
+        })
+    }
+}
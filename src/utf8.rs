@@ -0,0 +1,136 @@
+use core::str;
+use byteorder::ByteOrder;
+use either::Either;
+use serde::{Serialize, Serializer, ser::SerializeTuple};
+use super::{
+    io::{
+        Read,
+        BinarySerializerDelegate,
+        BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+        read_small,
+    },
+    de::BinaryDeserializerError,
+};
+
+/// A char written as the length of its UTF-8 encoding followed by the encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Utf8Char(char);
+
+impl Serialize for Utf8Char {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buffer = [0; 4];
+        let bytes = self.0.encode_utf8(&mut buffer).as_bytes();
+        [bytes.len() as u8]
+            .iter()
+            .chain(bytes)
+            .try_fold(serializer.serialize_tuple(1 + bytes.len())?, |mut t, byte| {
+                t.serialize_element(byte).map(|()| t)
+            })
+            .and_then(SerializeTuple::end)
+    }
+}
+
+// the char of `[length, bytes..]`, or the length or the first byte if it is not one char
+fn decode_utf8(length: u8, bytes: &[u8]) -> Result<char, u32> {
+    let first = bytes.first().cloned().unwrap_or(length);
+    str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        })
+        .ok_or(u32::from(first))
+}
+
+/// Writes a char as its UTF-8 encoding prefixed with the length of it, 1 to 4.
+///
+/// An ASCII char takes 2 bytes instead of 4. The deserializer reads the length first
+/// and then exactly that many bytes, everything else keeps the default encoding.
+pub struct Utf8CharDelegate;
+
+impl BinarySerializerDelegate for Utf8CharDelegate {
+    type Variant = u32;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = Utf8Char;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        v
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        v
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        Utf8Char(v)
+    }
+}
+
+impl BinaryDeserializerDelegate for Utf8CharDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    // the widest char, the actual width is in the first byte
+    fn char_size() -> usize {
+        5
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        let length = bytes[0];
+        bytes[1..]
+            .get(..usize::from(length))
+            .ok_or(u32::from(length))
+            .and_then(|utf8| decode_utf8(length, utf8))
+    }
+
+    fn read_char<'de, R, E>(read: &mut R) -> Result<char, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        let length = read_small::<_, Self::SmallBuffer, _, _>(read, 1, |b| b[0])?;
+        if !(1..=4).contains(&length) {
+            return Err(Either::Left(BinaryDeserializerError::WrongChar(u32::from(length))));
+        }
+        read_small::<_, Self::SmallBuffer, _, _>(read, usize::from(length), |b| decode_utf8(length, b))
+            .and_then(|v| v
+                .map_err(BinaryDeserializerError::WrongChar)
+                .map_err(Either::Left)
+            )
+    }
+}
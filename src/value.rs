@@ -0,0 +1,117 @@
+use core::fmt;
+use serde::{
+    ser::{Serialize, Serializer},
+    de::{Deserialize, Deserializer, Visitor, Error},
+};
+
+/// A primitive of any type, decoded by its tag.
+///
+/// Only the primitives are self-describing, with `PrimitiveTagDelegate`, so a `Value` is a
+/// single scalar. It deserializes with `deserialize_any` and serializes again as the same
+/// type, a blob of tagged primitives is a sequence of values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::I8(v) => serializer.serialize_i8(*v),
+            Value::I16(v) => serializer.serialize_i16(*v),
+            Value::I32(v) => serializer.serialize_i32(*v),
+            Value::I64(v) => serializer.serialize_i64(*v),
+            Value::I128(v) => serializer.serialize_i128(*v),
+            Value::U8(v) => serializer.serialize_u8(*v),
+            Value::U16(v) => serializer.serialize_u16(*v),
+            Value::U32(v) => serializer.serialize_u32(*v),
+            Value::U64(v) => serializer.serialize_u64(*v),
+            Value::U128(v) => serializer.serialize_u128(*v),
+            Value::F32(v) => serializer.serialize_f32(*v),
+            Value::F64(v) => serializer.serialize_f64(*v),
+            Value::Char(v) => serializer.serialize_char(*v),
+            Value::Str(v) => serializer.serialize_str(v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+macro_rules! visit {
+    ($method:ident, $t:ty, $variant:ident) => {
+        fn $method<E>(self, v: $t) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(Value::$variant(v))
+        }
+    };
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a primitive")
+    }
+
+    visit!(visit_bool, bool, Bool);
+    visit!(visit_i8, i8, I8);
+    visit!(visit_i16, i16, I16);
+    visit!(visit_i32, i32, I32);
+    visit!(visit_i64, i64, I64);
+    visit!(visit_i128, i128, I128);
+    visit!(visit_u8, u8, U8);
+    visit!(visit_u16, u16, U16);
+    visit!(visit_u32, u32, U32);
+    visit!(visit_u64, u64, U64);
+    visit!(visit_u128, u128, U128);
+    visit!(visit_f32, f32, F32);
+    visit!(visit_f64, f64, F64);
+    visit!(visit_char, char, Char);
+    visit!(visit_string, String, Str);
+    visit!(visit_byte_buf, Vec<u8>, Bytes);
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Value::Str(v.to_owned()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Value::Bytes(v.to_owned()))
+    }
+}
@@ -0,0 +1,386 @@
+use byteorder::ByteOrder;
+use either::Either;
+use super::{
+    io::{
+        Read,
+        Write,
+        BinarySerializerDelegate,
+        BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+        read_small,
+    },
+    de::BinaryDeserializerError,
+    protobuf::VarintLength,
+};
+
+const MAX_VARINT_SIZE: usize = 10;
+
+pub(crate) fn encode_varint(v: u64) -> ([u8; MAX_VARINT_SIZE], usize) {
+    let mut buffer = [0; MAX_VARINT_SIZE];
+    let mut rest = v;
+    let mut position = 0;
+    loop {
+        let byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+        if rest == 0 {
+            buffer[position] = byte;
+            position += 1;
+            break;
+        } else {
+            buffer[position] = byte | 0x80;
+            position += 1;
+        }
+    }
+    (buffer, position)
+}
+
+pub(crate) fn write_varint<W>(write: &mut W, v: u64) -> Result<(), W::Error>
+where
+    W: Write,
+{
+    let (buffer, length) = encode_varint(v);
+    write.write(&buffer[..length])
+}
+
+pub(crate) fn read_varint<'de, R>(read: &mut R, max: u64) -> Result<u64, Either<BinaryDeserializerError, R::Error>>
+where
+    R: Read<'de>,
+{
+    let mut value = 0u64;
+    for index in 0..MAX_VARINT_SIZE {
+        let byte = read_small::<_, [u8; 1], _, _>(read, 1, |b| b[0])?;
+        let part = u64::from(byte & 0x7f);
+        let shift = 7 * index;
+        if shift == 63 && part > 1 {
+            break;
+        }
+        value |= part << shift;
+        if byte & 0x80 == 0 {
+            return if value > max {
+                Err(Either::Left(BinaryDeserializerError::InvalidVarint))
+            } else {
+                Ok(value)
+            };
+        }
+    }
+    Err(Either::Left(BinaryDeserializerError::InvalidVarint))
+}
+
+pub(crate) fn max_value(size: usize) -> u64 {
+    if size >= 8 {
+        u64::MAX
+    } else {
+        (1 << (8 * size)) - 1
+    }
+}
+
+/// Encodes every integer wider than one byte as unsigned LEB128.
+///
+/// Lengths, variants and chars are integers on the wire too, so they use the same codec.
+/// Signed integers are written as their two's complement bit pattern, so small negative
+/// values take the full varint width.
+pub struct VarintIntDelegate;
+
+impl BinarySerializerDelegate for VarintIntDelegate {
+    type Variant = u32;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        v
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        v
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        v
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+
+    fn write_integer<W, E>(write: &mut W, v: u64, size: usize) -> Result<(), W::Error>
+    where
+        W: Write,
+        E: ByteOrder,
+    {
+        let _ = size;
+        write_varint(write, v)
+    }
+}
+
+impl BinaryDeserializerDelegate for VarintIntDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn read_integer<'de, R, E>(read: &mut R, size: usize) -> Result<u64, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_varint(read, max_value(size))
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::variant_size()).map(|x| x as u32)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::length_size()).map(|x| x as usize)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::sequence_length_size()).map(|x| Some(x as usize))
+    }
+
+    fn read_char<'de, R, E>(read: &mut R) -> Result<char, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        Self::read_integer::<_, E>(read, Self::char_size()).and_then(|code| {
+            let code = code as u32;
+            core::char::from_u32(code)
+                .ok_or(Either::Left(BinaryDeserializerError::WrongChar(code)))
+        })
+    }
+}
+
+/// Encodes lengths, sequence lengths and enum and `Option` tags as unsigned LEB128, the
+/// counterpart of `VarintDeserializerDelegate`.
+///
+/// A length below 128 takes one byte instead of eight. Integers and chars keep the fixed
+/// width, see `VarintIntDelegate` to encode them as varints too.
+pub struct VarintSerializerDelegate;
+
+impl BinarySerializerDelegate for VarintSerializerDelegate {
+    type Variant = VarintLength;
+    type Length = VarintLength;
+    type SequenceLength = VarintLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        VarintLength::from(v as usize)
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        VarintLength::from(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        VarintLength::from(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+/// Decodes lengths, sequence lengths and enum and `Option` tags written by
+/// `VarintSerializerDelegate` one byte at a time.
+pub struct VarintDeserializerDelegate;
+
+impl BinaryDeserializerDelegate for VarintDeserializerDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_varint(read, max_value(Self::variant_size())).map(|x| x as u32)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_varint(read, max_value(Self::length_size())).map(|x| x as usize)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        read_varint(read, max_value(Self::sequence_length_size())).map(|x| Some(x as usize))
+    }
+}
+
+fn zigzag(v: u32) -> u32 {
+    (v << 1) ^ ((v as i32 >> 31) as u32)
+}
+
+fn unzigzag(v: u32) -> u32 {
+    (v >> 1) ^ (v & 1).wrapping_neg()
+}
+
+/// Like `VarintSerializerDelegate` and `VarintDeserializerDelegate`, but maps enum and
+/// `Option` tags through zigzag before LEB128.
+///
+/// Tags below 64 and the top of the `u32` range, which a signed discriminant cast to `u32`
+/// lands in, take one byte. Any `u32` tag still round-trips, in at most five bytes.
+pub struct ZigzagVarintDelegate;
+
+impl BinarySerializerDelegate for ZigzagVarintDelegate {
+    type Variant = VarintLength;
+    type Length = VarintLength;
+    type SequenceLength = VarintLength;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        VarintLength::from(zigzag(v) as usize)
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        VarintLength::from(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        VarintLength::from(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        v as _
+    }
+}
+
+impl BinaryDeserializerDelegate for ZigzagVarintDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        VarintDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        VarintDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        VarintDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        VarintDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        unzigzag(VarintDeserializerDelegate::decode_variant::<E>(bytes))
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        VarintDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        VarintDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        VarintDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn read_variant<'de, R, E>(read: &mut R) -> Result<u32, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        VarintDeserializerDelegate::read_variant::<R, E>(read).map(unzigzag)
+    }
+
+    fn read_length<'de, R, E>(read: &mut R) -> Result<usize, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        VarintDeserializerDelegate::read_length::<R, E>(read)
+    }
+
+    fn read_sequence_length<'de, R, E>(read: &mut R) -> Result<Option<usize>, Either<BinaryDeserializerError, R::Error>>
+    where
+        R: Read<'de>,
+        E: ByteOrder,
+    {
+        VarintDeserializerDelegate::read_sequence_length::<R, E>(read)
+    }
+}
@@ -0,0 +1,54 @@
+//! Encodes a `Weak` reference exactly like an `Option` of the value it points to.
+//!
+//! Use with `#[serde(with = "tirse::weak::rc")]` for `std::rc::Weak` or
+//! `#[serde(with = "tirse::weak::sync")]` for `std::sync::Weak`. Serialization upgrades the
+//! reference, a live one is written as `Some(value)` and a dead one as `None`.
+//!
+//! The round trip is lossy. Nothing in the input owns the decoded value, so a `Weak` that
+//! pointed at it would die at once. Deserialization consumes and validates the `Option`
+//! and always returns a dead `Weak`, the value itself is dropped. Rebuild the links after
+//! deserialization if the graph needs them.
+
+/// `#[serde(with)]` helpers for `std::rc::Weak`.
+pub mod rc {
+    use std::rc::Weak;
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    pub fn serialize<T, S>(weak: &Weak<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        weak.upgrade().as_deref().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Weak<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(|_| Weak::new())
+    }
+}
+
+/// `#[serde(with)]` helpers for `std::sync::Weak`.
+pub mod sync {
+    use std::sync::Weak;
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    pub fn serialize<T, S>(weak: &Weak<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        weak.upgrade().as_deref().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Weak<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(|_| Weak::new())
+    }
+}
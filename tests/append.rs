@@ -0,0 +1,81 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use tirse::WriteWrapper;
+use tirse::OffsetWrite;
+use tirse::Cursor;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type AppendSerializer = DefaultBinarySerializer<OffsetWrite<WriteWrapper<Vec<u8>>>, String>;
+type DeserializeFromCursor<'a> = DefaultBinaryDeserializer<'a, Cursor<&'a [u8]>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Record {
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn test_append_with_index() {
+    let records = [
+        Record { id: 1, name: "first".to_owned() },
+        Record { id: 2, name: "second record".to_owned() },
+        Record { id: 3, name: String::new() },
+    ];
+
+    let mut index = Vec::new();
+    let mut serializer = AppendSerializer::new(WriteWrapper::from(Vec::new()));
+    for record in &records[..2] {
+        index.push(serializer.position());
+        serializer = record.serialize(serializer).unwrap();
+    }
+    let file = serializer.consume().into_inner().into_inner();
+
+    // continue the same file later
+    let position = file.len();
+    let write = OffsetWrite::with_position(WriteWrapper::from(file), position);
+    let serializer = AppendSerializer::new(write);
+    index.push(serializer.position());
+    let file = records[2].serialize(serializer).unwrap().consume().into_inner().into_inner();
+
+    assert_eq!(index[0], 0);
+    assert_eq!(index[2], position);
+
+    for (&offset, record) in index.iter().zip(records.iter()).rev() {
+        let mut cursor = Cursor::new(file.as_slice());
+        cursor.set_position(offset);
+        let r = Record::deserialize(DeserializeFromCursor::new(cursor)).unwrap();
+        assert_eq!(&r, record);
+    }
+}
+
+#[test]
+fn test_bytes_written() {
+    use tirse::CountingWrite;
+
+    #[derive(Serialize)]
+    pub struct Point3d {
+        x: u32,
+        y: u32,
+        z: u32,
+    }
+
+    let point = Point3d { x: 1, y: 2, z: 3 };
+
+    let serializer = point.serialize(AppendSerializer::new(WriteWrapper::from(Vec::new()))).unwrap();
+    assert_eq!(serializer.bytes_written(), 12);
+
+    // appending after existing data counts only the new bytes
+    let write = OffsetWrite::with_position(WriteWrapper::from(vec![0; 5]), 5);
+    let serializer = point.serialize(AppendSerializer::new(write)).unwrap();
+    assert_eq!(serializer.bytes_written(), 12);
+    assert_eq!(serializer.position(), 17);
+
+    let serializer = DefaultBinarySerializer::<CountingWrite, String>::new(CountingWrite::default());
+    assert_eq!(point.serialize(serializer).unwrap().bytes_written(), 12);
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "use_std")]
+
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
+
+use tirse::to_vec;
+use tirse::DefaultBinaryDeserializer;
+
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    requests: AtomicU64,
+    running: AtomicBool,
+}
+
+#[test]
+fn test_atomics_as_inner_value() {
+    let requests = AtomicU64::new(0x0102_0304_0506_0708);
+    assert_eq!(to_vec(&requests).unwrap(), to_vec(&0x0102_0304_0506_0708u64).unwrap());
+    assert_eq!(to_vec(&AtomicBool::new(true)).unwrap(), to_vec(&true).unwrap());
+
+    let snapshot = Snapshot { requests, running: AtomicBool::new(true) };
+    let v = to_vec(&snapshot).unwrap();
+    assert_eq!(v.len(), 9);
+
+    let r = Snapshot::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r.requests.load(Ordering::SeqCst), 0x0102_0304_0506_0708);
+    assert!(r.running.load(Ordering::SeqCst));
+}
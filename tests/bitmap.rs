@@ -0,0 +1,137 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::ByteOrder;
+
+use tirse::DefaultEndian;
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::OptionBitmapDelegate;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+
+type BitmapSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, DefaultEndian, OptionBitmapDelegate<DefaultBinarySerializerDelegate>, String>;
+type BitmapDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, DefaultEndian, OptionBitmapDelegate<DefaultBinaryDeserializerDelegate>, String>;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Settings {
+    id: u8,
+    width: Option<u16>,
+    height: Option<u16>,
+    depth: Option<u16>,
+    tags: Vec<Option<u8>>,
+    title: Option<Option<u8>>,
+    a: Option<u8>,
+    b: Option<u8>,
+    c: Option<u8>,
+}
+
+#[test]
+fn test_option_bitmap() {
+    let settings = Settings {
+        id: 1,
+        width: Some(0x0102),
+        height: None,
+        depth: Some(0x0304),
+        tags: vec![None, Some(5)],
+        title: Some(None),
+        a: None,
+        b: None,
+        c: Some(6),
+    };
+
+    let v = settings.serialize(BitmapSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    let mut expected = vec![0b0010_1010, 0b0000_0001, 1];
+    let u16_bytes = |x| {
+        let mut b = [0; 2];
+        DefaultEndian::write_u16(&mut b, x);
+        b
+    };
+    expected.extend_from_slice(&u16_bytes(0x0102));
+    expected.extend_from_slice(&u16_bytes(0x0304));
+    let u32_bytes = |x| {
+        let mut b = [0; 4];
+        DefaultEndian::write_u32(&mut b, x);
+        b
+    };
+    let mut length = [0; 8];
+    DefaultEndian::write_u64(&mut length, 2);
+    // options nested in a sequence and in an option keep their tag
+    expected.extend_from_slice(&length);
+    expected.extend_from_slice(&u32_bytes(0));
+    expected.extend_from_slice(&u32_bytes(1));
+    expected.push(5);
+    expected.extend_from_slice(&u32_bytes(0));
+    expected.push(6);
+    assert_eq!(v, expected);
+
+    let r = Settings::deserialize(BitmapDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, settings);
+}
+
+#[test]
+fn test_option_bitmap_nested_struct() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct Outer {
+        inner: Option<Settings>,
+        last: Option<u32>,
+    }
+
+    let outer = Outer {
+        inner: Some(Settings {
+            id: 2,
+            width: None,
+            height: Some(7),
+            depth: None,
+            tags: vec![],
+            title: None,
+            a: Some(1),
+            b: Some(2),
+            c: None,
+        }),
+        last: None,
+    };
+
+    let v = outer.serialize(BitmapSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..3], [0b01, 0b1100_0100, 0]);
+
+    let r = Outer::deserialize(BitmapDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, outer);
+}
+
+#[test]
+fn test_option_bitmap_after_failed_field() {
+    use serde::ser::{self, Serializer, SerializeStruct};
+    use either::Either;
+    use tirse::{ErrorAdapter, BinarySerializerError};
+
+    pub struct Broken;
+
+    impl Serialize for Broken {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let _ = serializer;
+            Err(ser::Error::custom("broken"))
+        }
+    }
+
+    let mut s = BitmapSerializer::new(Vec::new()).serialize_struct("Broken", 2).unwrap();
+    assert!(matches!(s.serialize_field("broken", &Broken), Err(ErrorAdapter::Outer(ref e)) if e == "broken"));
+    match s.serialize_field("next", &Some(1u8)) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PreviousFieldFailed))) => (),
+        r => panic!("expected the previous field failed, got {:?}", r.map(|_| ())),
+    }
+    match s.end() {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PreviousFieldFailed))) => (),
+        r => panic!("expected the previous field failed, got {:?}", r.map(|_| ())),
+    }
+}
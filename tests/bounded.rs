@@ -0,0 +1,50 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde_derive::Serialize;
+
+use either::Either;
+
+use tirse::BoundedWrite;
+use tirse::BudgetExceeded;
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::DefaultBinarySerializer;
+
+type SerializerIntoPacket = DefaultBinarySerializer<BoundedWrite<WriteWrapper<Vec<u8>>>, String>;
+
+#[derive(Serialize)]
+pub struct Reading {
+    sensor: u16,
+    value: u32,
+    status: u8,
+    flags: u8,
+}
+
+#[test]
+fn test_fits_exactly() {
+    let reading = Reading { sensor: 1, value: 2, status: 3, flags: 4 };
+    let write = BoundedWrite::new(WriteWrapper::from(Vec::new()), 8);
+    let write = reading.serialize(SerializerIntoPacket::new(write)).unwrap().consume();
+    assert_eq!(write.written(), 8);
+    assert_eq!(write.remaining(), 0);
+    assert_eq!(write.into_inner().into_inner().len(), 8);
+}
+
+#[test]
+fn test_barely_overflows() {
+    #[derive(Serialize)]
+    pub struct Packet {
+        reading: Reading,
+        checksum: u16,
+    }
+
+    let packet = Packet { reading: Reading { sensor: 1, value: 2, status: 3, flags: 4 }, checksum: 5 };
+    let write = BoundedWrite::new(WriteWrapper::from(Vec::new()), 8);
+    match packet.serialize(SerializerIntoPacket::new(write)) {
+        Err(ErrorAdapter::Inner(Either::Right(Either::Left(e)))) => {
+            assert_eq!(e, BudgetExceeded { attempted: 2, remaining: 0 });
+        },
+        r => panic!("unexpected {:?}", r.map(|_| ())),
+    }
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+
+use std::io;
+
+use tirse::WriteWrapper;
+use tirse::BufferedWriteWrapper;
+use tirse::DefaultBinarySerializer;
+
+#[derive(Default, Debug)]
+pub struct CountingWriter {
+    data: Vec<u8>,
+    writes: usize,
+}
+
+impl io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_buffered_writes() {
+    let value = (0..1000u32).map(|x| (x, x as u8)).collect::<Vec<_>>();
+
+    let direct = value
+        .serialize(DefaultBinarySerializer::<WriteWrapper<CountingWriter>, String>::new(CountingWriter::default()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    assert_eq!(direct.writes, 2001);
+
+    let write = BufferedWriteWrapper::with_capacity(1024, CountingWriter::default());
+    let buffered = value
+        .serialize(DefaultBinarySerializer::<BufferedWriteWrapper<CountingWriter>, String>::new(write))
+        .unwrap()
+        .consume()
+        .into_inner()
+        .unwrap();
+    assert_eq!(buffered.writes, 5);
+    assert_eq!(buffered.data, direct.data);
+}
+
+#[test]
+fn test_flush_on_drop() {
+    let mut output = Vec::new();
+    {
+        let serializer = DefaultBinarySerializer::<BufferedWriteWrapper<&mut Vec<u8>>, String>::new(&mut output);
+        let _ = 0x01020304u32.serialize(serializer).unwrap();
+    }
+    assert_eq!(output, default_bytes!(0x01020304u32));
+
+    let mut output = Vec::new();
+    let mut write = BufferedWriteWrapper::with_capacity(4, &mut output);
+    tirse::Write::write(&mut write, &[1, 2, 3, 4, 5, 6]).unwrap();
+    tirse::Write::write(&mut write, &[7]).unwrap();
+    write.flush().unwrap();
+    drop(write);
+    assert_eq!(output, [1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn test_bytes_single_write() {
+    pub struct Blob(Vec<u8>);
+
+    impl Serialize for Blob {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+
+    let blob = Blob((0..0x10000).map(|x| x as u8).collect());
+    let write = blob
+        .serialize(DefaultBinarySerializer::<WriteWrapper<CountingWriter>, String>::new(CountingWriter::default()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    assert_eq!(write.writes, 2);
+    assert_eq!(write.data[..8], default_bytes!(0x10000u64));
+    assert_eq!(write.data[8..], blob.0[..]);
+}
+
+#[test]
+fn test_finish_flushes() {
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone, Default)]
+    pub struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let sink = Shared::default();
+    let serializer = DefaultBinarySerializer::<BufferedWriteWrapper<Shared>, String>::new(sink.clone());
+    let serializer = (1u32, 2u16).serialize(serializer).unwrap();
+    assert!(sink.0.borrow().is_empty());
+
+    let write = serializer.finish().unwrap();
+    let mut expected = default_bytes!(1u32).to_vec();
+    expected.extend_from_slice(&default_bytes!(2u16));
+    assert_eq!(*sink.0.borrow(), expected);
+    drop(write);
+    assert_eq!(*sink.0.borrow(), expected);
+}
+
+#[test]
+fn test_into_inner_error() {
+    use std::{cell::Cell, rc::Rc};
+
+    #[derive(Debug)]
+    pub struct Failing {
+        fail: Rc<Cell<bool>>,
+        data: Vec<u8>,
+    }
+
+    impl io::Write for Failing {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.fail.get() {
+                return Err(io::Error::other("failing"));
+            }
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let fail = Rc::new(Cell::new(true));
+    let mut write = BufferedWriteWrapper::with_capacity(16, Failing { fail: fail.clone(), data: Vec::new() });
+    tirse::Write::write(&mut write, &[1, 2, 3]).unwrap();
+
+    // the wrapper comes back with the error and the buffered bytes
+    let (write, e) = write.into_inner().unwrap_err();
+    assert_eq!(e.to_string(), "failing");
+    assert!(format!("{:?}", write).contains("buffer: [1, 2, 3]"));
+
+    fail.set(false);
+    let inner = write.into_inner().unwrap();
+    assert_eq!(inner.data, [1, 2, 3]);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::ReadWrapper;
+use tirse::ChainRead;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Message<'a> {
+    id: u64,
+    kind: u16,
+    name: &'a str,
+}
+
+#[test]
+fn test_chain_across_boundary() {
+    let message = Message { id: 0x0102030405060708, kind: 9, name: "body" };
+    let v = message.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    // the `id` is split between the header and the body, the `name` is in the body
+    for boundary in [3, 8, 10] {
+        let (header, body) = v.split_at(boundary);
+        let read = ChainRead::new(header.iter(), body.iter());
+        let r = Message::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).unwrap();
+        assert_eq!(r, message);
+        assert_eq!(r.name.as_ptr(), v[v.len() - 4..].as_ptr());
+    }
+
+    // a string across the boundary cannot be borrowed
+    let (header, body) = v.split_at(v.len() - 2);
+    let read = ChainRead::new(header.iter(), body.iter());
+    assert!(Message::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).is_err());
+
+    let (header, body) = v.split_at(v.len() - 2);
+    let read = ChainRead::new(header.iter(), body.iter());
+    let r = <(u64, u16, String)>::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).unwrap();
+    assert_eq!(r, (message.id, message.kind, message.name.to_owned()));
+}
+
+#[test]
+fn test_chain_header_and_stream() {
+    let v = (7u32, 0xaabbu16, "streamed".to_owned())
+        .serialize(SerializerIntoVec::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+
+    let (header, body) = v.split_at(5);
+    let read = ChainRead::<Iter<u8>, ReadWrapper<&[u8]>>::new(header.iter(), ReadWrapper::from(body));
+    let r = <(u32, u16, String)>::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).unwrap();
+    assert_eq!(r, (7, 0xaabb, "streamed".to_owned()));
+}
+
+#[test]
+fn test_chain_stream_header() {
+    use std::io::Cursor;
+
+    let message = Message { id: 0x0102030405060708, kind: 9, name: "body" };
+    let v = message.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    // the header does not know its length, so it is read up to its end and the value
+    // continues in the body
+    for boundary in [0, 3, 8, 10, v.len() - 2, v.len()] {
+        let (header, body) = v.split_at(boundary);
+        let read = ChainRead::new(ReadWrapper::from(Cursor::new(header.to_vec())), body.iter());
+        let r = <(u64, u16, String)>::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).unwrap();
+        assert_eq!(r, (message.id, message.kind, message.name.to_owned()));
+    }
+
+    let (header, body) = v.split_at(3);
+    let read = ChainRead::new(ReadWrapper::from(Cursor::new(header.to_vec())), body[..body.len() - 1].iter());
+    assert!(<(u64, u16, String)>::deserialize(DefaultBinaryDeserializer::<_, String>::new(read)).is_err());
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromIter<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut Iter<'a, u8>, String>;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    data: [u8; 8],
+    crc: u32,
+}
+
+fn write_record(data: [u8; 8]) -> Vec<u8> {
+    let s = SerializerIntoVec::new(Vec::new());
+    let (s, bytes) = s.serialize_captured(&data).unwrap();
+    crc32(&bytes).serialize(s).unwrap().consume().into_inner()
+}
+
+fn read_record(v: &[u8]) -> Result<Record, String> {
+    let mut iter = v.iter();
+    let mut d = DeserializeFromIter::new(&mut iter);
+    let (data, bytes) = d.deserialize_captured::<[u8; 8]>().map_err(|e| e.to_string())?;
+    let crc = u32::deserialize(d.split()).map_err(|e| e.to_string())?;
+    if crc32(&bytes) == crc {
+        Ok(Record { data, crc })
+    } else {
+        Err("checksum mismatch".to_owned())
+    }
+}
+
+#[test]
+fn test_checksum_field() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+    let data = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut v = write_record(data);
+    assert_eq!(v.len(), 12);
+    assert_eq!(read_record(&v), Ok(Record { data, crc: crc32(&data) }));
+
+    v[3] ^= 0x10;
+    assert_eq!(read_record(&v), Err("checksum mismatch".to_owned()));
+}
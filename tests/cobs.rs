@@ -0,0 +1,70 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use tirse::CobsWrite;
+use tirse::CobsRead;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type CobsSerializer<'a, 'b> = DefaultBinarySerializer<&'b mut CobsWrite<&'a mut Vec<u8>>, String>;
+type CobsDeserializer<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut CobsRead<'a, Iter<'a, u8>>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Reading {
+    sensor: u8,
+    value: u32,
+    label: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Blob {
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_two_frames() {
+    let reading = Reading { sensor: 0, value: 0x100, label: "temp".to_owned() };
+    let blob = Blob { data: (1..=255).chain(0..3).collect() };
+
+    let mut stream = Vec::new();
+    let mut w = CobsWrite::new(&mut stream);
+    reading.serialize(CobsSerializer::new(&mut w)).unwrap();
+    w.end_frame().unwrap();
+    blob.serialize(CobsSerializer::new(&mut w)).unwrap();
+    w.end_frame().unwrap();
+
+    // only the delimiters are zero
+    let zeros = stream.iter().enumerate().filter(|&(_, &b)| b == 0).map(|(i, _)| i).collect::<Vec<_>>();
+    assert_eq!(zeros.len(), 2);
+    assert_eq!(zeros[1], stream.len() - 1);
+
+    let mut r = CobsRead::new(stream.iter());
+    assert_eq!(Reading::deserialize(CobsDeserializer::new(&mut r)).unwrap(), reading);
+    r.next_frame().unwrap();
+    assert_eq!(Blob::deserialize(CobsDeserializer::new(&mut r)).unwrap(), blob);
+    r.next_frame().unwrap();
+    assert!(r.get_ref().as_slice().is_empty());
+}
+
+#[test]
+fn test_frame_overrun() {
+    let mut stream = Vec::new();
+    let mut w = CobsWrite::new(&mut stream);
+    7u8.serialize(CobsSerializer::new(&mut w)).unwrap();
+    w.end_frame().unwrap();
+    9u8.serialize(CobsSerializer::new(&mut w)).unwrap();
+    w.end_frame().unwrap();
+    assert_eq!(stream, [2, 7, 0, 2, 9, 0]);
+
+    // the second frame is not read as the rest of the first one
+    let mut r = CobsRead::new(stream.iter());
+    assert!(u16::deserialize(CobsDeserializer::new(&mut r)).is_err());
+    r.next_frame().unwrap();
+    assert_eq!(u8::deserialize(CobsDeserializer::new(&mut r)).unwrap(), 9);
+}
@@ -0,0 +1,24 @@
+/// The bytes of the integer `$v` in `DefaultEndian`, the byte order of the `Default*`
+/// serializers, which the features `default_le` and `default_be` select.
+#[allow(unused_macros)]
+macro_rules! default_bytes {
+    ($v:expr) => {{
+        use byteorder::ByteOrder;
+
+        let v = $v;
+        if tirse::DefaultEndian::read_u16(&[1, 0]) == 1 {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        }
+    }};
+}
+
+/// Serializes `$value` into a new vector with `$serializer`, a serializer type over
+/// `WriteWrapper<Vec<u8>>`.
+#[allow(unused_macros)]
+macro_rules! to_vec {
+    ($serializer:ty, $value:expr) => {
+        serde::Serialize::serialize($value, <$serializer>::new(Vec::new())).unwrap().consume().into_inner()
+    };
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+use serde::Serializer;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::CompactVariantDelegate;
+
+type CompactSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, CompactVariantDelegate, String>;
+type CompactDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, CompactVariantDelegate, String>;
+
+#[test]
+fn test_option_is_five_bytes() {
+    let v = to_vec!(CompactSerializer, &Some(7u32));
+    assert_eq!(v, vec![1, 7, 0, 0, 0]);
+    let r = Option::<u32>::deserialize(CompactDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, Some(7));
+
+    let v = to_vec!(CompactSerializer, &None::<u32>);
+    assert_eq!(v, vec![0]);
+    let r = Option::<u32>::deserialize(CompactDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, None);
+}
+
+#[test]
+fn test_enum_variant_is_one_byte() {
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    pub enum Event {
+        Start,
+        Stop(u16),
+        Rename { name: String },
+    }
+
+    let value = vec![Event::Stop(3), Event::Start, Event::Rename { name: "a".to_owned() }];
+    let v = to_vec!(CompactSerializer, &value);
+    assert_eq!(&v[8..11], &[1, 3, 0]);
+    assert_eq!(v[11], 0);
+    assert_eq!(v[12], 2);
+    let r = Vec::<Event>::deserialize(CompactDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+
+    let r = CompactSerializer::new(Vec::new()).serialize_unit_variant("Wide", 256, "V");
+    assert!(r.is_err());
+}
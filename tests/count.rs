@@ -0,0 +1,85 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+struct CountingAllocator;
+
+thread_local! {
+    // the harness allocates on other threads meanwhile, count only this one
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_count_without_allocating() {
+    let v = (0..10_000u32).collect::<Vec<_>>();
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let r = DeserializeFromSlice::new(bytes.iter()).count_elements::<u32>().unwrap();
+    let after = ALLOCATIONS.with(Cell::get);
+
+    assert_eq!(r, (10_000, bytes.len()));
+    assert_eq!(after, before);
+
+    assert!(DeserializeFromSlice::new(bytes[..bytes.len() - 1].iter()).count_elements::<u32>().is_err());
+}
+
+#[test]
+fn test_serialized_size() {
+    use serde_derive::Serialize;
+    use tirse::serialized_size;
+
+    #[derive(Serialize)]
+    pub enum Shape {
+        Point,
+        Circle { radius: u32 },
+        Path(Vec<(i16, i16)>),
+    }
+
+    #[derive(Serialize)]
+    pub struct Scene {
+        name: String,
+        shapes: Vec<Shape>,
+        background: Option<[u8; 3]>,
+        tags: std::collections::BTreeMap<String, u64>,
+    }
+
+    let scene = Scene {
+        name: "scene".to_owned(),
+        shapes: vec![Shape::Point, Shape::Circle { radius: 4 }, Shape::Path(vec![(1, 2), (3, -4)])],
+        background: None,
+        tags: vec![("a".to_owned(), 1), ("bc".to_owned(), 2)].into_iter().collect(),
+    };
+
+    let bytes = scene.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(serialized_size::<_, String>(&scene).unwrap(), bytes.len());
+
+    let bytes = "abc".serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(serialized_size::<_, String>("abc").unwrap(), bytes.len());
+    assert_eq!(serialized_size::<_, String>(&()).unwrap(), 0);
+}
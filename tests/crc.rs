@@ -0,0 +1,59 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::Write;
+use tirse::Crc32Write;
+use tirse::Crc32Read;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type CrcSerializer<'a> = DefaultBinarySerializer<Crc32Write<&'a mut Vec<u8>>, String>;
+type CrcDeserializer<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut Crc32Read<'a, Iter<'a, u8>>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Record {
+    id: u32,
+    payload: Vec<u8>,
+}
+
+#[test]
+fn test_check_value() {
+    let mut v = Vec::new();
+    let mut w = Crc32Write::new(&mut v);
+    w.write(b"12345").unwrap();
+    w.write(b"6789").unwrap();
+    assert_eq!(w.crc(), 0xcbf43926);
+    w.finalize().unwrap();
+    assert_eq!(v[9..], 0xcbf43926u32.to_le_bytes());
+}
+
+#[test]
+fn test_corrupted_payload() {
+    let record = Record { id: 7, payload: b"persisted".to_vec() };
+
+    let mut v = Vec::new();
+    record.serialize(CrcSerializer::new(Crc32Write::new(&mut v))).unwrap().consume().finalize().unwrap();
+
+    let mut r = Crc32Read::new(v.iter());
+    assert_eq!(Record::deserialize(CrcDeserializer::new(&mut r)).unwrap(), record);
+    assert!(r.verify().unwrap().as_slice().is_empty());
+
+    let mut corrupted = v.clone();
+    corrupted[12] ^= 0x20;
+    let mut r = Crc32Read::new(corrupted.iter());
+    let value = Record::deserialize(CrcDeserializer::new(&mut r)).unwrap();
+    assert_ne!(value, record);
+    match r.verify() {
+        Err(Either::Left(BinaryDeserializerError::ChecksumMismatch { expected, computed })) => assert_ne!(expected, computed),
+        r => panic!("expected checksum mismatch, got {:?}", r),
+    }
+}
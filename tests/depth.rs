@@ -0,0 +1,130 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializerError;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize)]
+pub struct Node {
+    value: u8,
+    next: Option<Box<Node>>,
+}
+
+fn list(length: usize) -> Node {
+    (1..length).fold(Node { value: 0, next: None }, |next, value| Node {
+        value: value as u8,
+        next: Some(Box::new(next)),
+    })
+}
+
+#[test]
+fn test_serialize_depth_limit() {
+    // every node is a struct and an option, two levels
+    let r = list(10).serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(20));
+    assert_eq!(r.unwrap().consume().into_inner().len(), 10 + 9 * 4 + 4);
+
+    let r = list(11).serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(20));
+    match r {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::DepthLimitExceeded))) => (),
+        _ => panic!("expected depth limit"),
+    }
+
+    let deep = vec![list(1000)];
+    assert!(deep.serialize(SerializerIntoVec::new(Vec::new())).is_err());
+    let nested = (((((1u8,),),),),);
+    assert!(nested.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(5)).is_ok());
+    assert!(nested.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(4)).is_err());
+}
+
+#[test]
+fn test_deserialize_depth_limit() {
+    let value = vec![vec![Some(1u8)], vec![None]];
+    let v = value.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    // two sequences and an option, three levels
+    let r = Vec::<Vec<Option<u8>>>::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(3)).unwrap();
+    assert_eq!(r, value);
+
+    let r = Vec::<Vec<Option<u8>>>::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(2)).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)));
+}
+
+#[test]
+fn test_hostile_nesting() {
+    #[derive(Deserialize, Debug)]
+    pub struct Chain(Option<Box<Chain>>);
+
+    // ten thousand `Some` would overflow the stack without the limit
+    let mut v = (0..10_000).flat_map(|_| default_bytes!(1u32)).collect::<Vec<u8>>();
+    v.extend_from_slice(&default_bytes!(0u32));
+    let r = Chain::deserialize(DeserializeFromSlice::new(v.iter())).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)));
+
+    let r = Chain::deserialize(DeserializeFromSlice::new(v[v.len() - 12..].iter())).unwrap();
+    assert!(r.0.unwrap().0.unwrap().0.is_none());
+}
+
+#[test]
+fn test_enum_depth_round_trip() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub enum E {
+        A(u8),
+        B,
+    }
+
+    // a unit variant has no payload and takes no level, the two options take two
+    let value = Some(Some(E::B));
+    let v = value.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(2)).unwrap().consume().into_inner();
+    let r = Option::<Option<E>>::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(2)).unwrap();
+    assert_eq!(r, value);
+
+    // the payload of a newtype variant is one more level
+    let value = Some(Some(E::A(7)));
+    let v = value.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(3)).unwrap().consume().into_inner();
+    let r = Option::<Option<E>>::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(3)).unwrap();
+    assert_eq!(r, value);
+
+    match value.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(2)) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::DepthLimitExceeded))) => (),
+        _ => panic!("expected depth limit"),
+    }
+    let r = Option::<Option<E>>::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(2)).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)));
+}
+
+#[test]
+fn test_newtype_depth_round_trip() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct N(Option<Box<N>>);
+
+    // a newtype takes no level, only its option does
+    let value = (0..64).fold(N(None), |next, _| N(Some(Box::new(next))));
+    let v = value.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(64)).unwrap().consume().into_inner();
+    let r = N::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(64)).unwrap();
+    assert_eq!(r, value);
+
+    match value.serialize(SerializerIntoVec::new(Vec::new()).with_max_depth(63)) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::DepthLimitExceeded))) => (),
+        _ => panic!("expected depth limit"),
+    }
+    let r = N::deserialize(DeserializeFromSlice::new(v.iter()).with_max_depth(63)).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)));
+}
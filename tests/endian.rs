@@ -0,0 +1,81 @@
+#![cfg(feature = "use_std")]
+
+use std::slice::Iter;
+
+use byteorder::{LittleEndian, BigEndian, NetworkEndian};
+
+use tirse::BinaryDeserializer;
+use tirse::DefaultBinaryDeserializerDelegate;
+use tirse::Endianness;
+
+type DeserializeFromSlice<'a, E> = BinaryDeserializer<'a, Iter<'a, u8>, E, DefaultBinaryDeserializerDelegate, String>;
+
+#[test]
+fn test_byte_order() {
+    let v = [];
+
+    let d = DeserializeFromSlice::<LittleEndian>::new(v.iter());
+    assert_eq!(d.byte_order(), Endianness::Little);
+
+    let d = DeserializeFromSlice::<BigEndian>::new(v.iter());
+    assert_eq!(d.byte_order(), Endianness::Big);
+
+    let d = DeserializeFromSlice::<NetworkEndian>::new(v.iter());
+    assert_eq!(d.byte_order(), Endianness::Big);
+}
+
+#[test]
+fn test_swap_endianness() {
+    use serde::Serialize;
+    use serde_derive::{Serialize, Deserialize};
+    use tirse::{BinarySerializer, WriteWrapper, DefaultBinarySerializerDelegate, swap_endianness};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    pub struct Sample {
+        id: u32,
+        value: i16,
+        ratio: f64,
+        name: String,
+    }
+
+    let sample = Sample { id: 0x01020304, value: -2, ratio: 0.5, name: "le".to_owned() };
+    let le = sample
+        .serialize(BinarySerializer::<WriteWrapper<Vec<u8>>, LittleEndian, DefaultBinarySerializerDelegate, String>::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    let be = sample
+        .serialize(BinarySerializer::<WriteWrapper<Vec<u8>>, BigEndian, DefaultBinarySerializerDelegate, String>::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    assert_ne!(le, be);
+
+    let swapped = swap_endianness::<Sample>(&le, Endianness::Little, Endianness::Big).unwrap();
+    assert_eq!(swapped, be);
+    let back = swap_endianness::<Sample>(&swapped, Endianness::Big, Endianness::Little).unwrap();
+    assert_eq!(back, le);
+
+    assert!(swap_endianness::<Sample>(&le[..3], Endianness::Little, Endianness::Big).is_err());
+}
+
+#[test]
+fn test_default_endian() {
+    use serde::Serialize;
+    use tirse::{DefaultBinarySerializer, WriteWrapper};
+
+    let v = 0x01020304u32
+        .serialize(DefaultBinarySerializer::<WriteWrapper<Vec<u8>>, String>::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+
+    let expected = if cfg!(feature = "default_le") {
+        [4, 3, 2, 1]
+    } else if cfg!(feature = "default_be") {
+        [1, 2, 3, 4]
+    } else {
+        0x01020304u32.to_ne_bytes()
+    };
+    assert_eq!(v, expected);
+}
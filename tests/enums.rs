@@ -0,0 +1,281 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::to_vec;
+use tirse::ErrorAdapter;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinaryDeserializer;
+
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub enum Open {
+    Red,
+    #[serde(other)]
+    Unknown,
+}
+
+#[test]
+fn test_out_of_range_variant() {
+    let v = to_vec(&5u32).unwrap();
+    let r = Color::deserialize(DeserializeFromSlice::new(v.iter()));
+    match r {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(5)))) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+
+    let v = to_vec(&Color::Blue).unwrap();
+    assert_eq!(Color::deserialize(DeserializeFromSlice::new(v.iter())).unwrap(), Color::Blue);
+    assert_eq!(Open::deserialize(DeserializeFromSlice::new(v.iter())).unwrap(), Open::Unknown);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Shape {
+    Point(Point),
+    Path(Vec<Point>),
+    Bytes(Vec<u8>),
+    Color(Color),
+    Tagged(Result<Color, Point>),
+}
+
+#[test]
+fn test_newtype_variants() {
+    let shapes = vec![
+        Shape::Point(Point { x: 1, y: -1 }),
+        Shape::Path(vec![Point { x: 0, y: 0 }, Point { x: 3, y: 4 }]),
+        Shape::Path(vec![]),
+        Shape::Bytes(vec![1, 2, 3]),
+        Shape::Color(Color::Green),
+        Shape::Tagged(Ok(Color::Red)),
+        Shape::Tagged(Err(Point { x: 5, y: 6 })),
+    ];
+
+    for shape in shapes {
+        let value = (shape, 0xaau8);
+        let v = to_vec(&value).unwrap();
+        let r = <(Shape, u8)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+        assert_eq!(r, value);
+    }
+
+    let v = to_vec(&Shape::Color(Color::Blue)).unwrap();
+    let mut expected = default_bytes!(3u32).to_vec();
+    expected.extend_from_slice(&default_bytes!(2u32));
+    assert_eq!(v, expected);
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Event {
+    Click { x: u32, y: u32 },
+    Key { code: u16, name: String, modifiers: Vec<u8> },
+    Quit,
+}
+
+#[test]
+fn test_struct_variants() {
+    let v = to_vec(&Event::Click { x: 10, y: 20 }).unwrap();
+    let mut expected = default_bytes!(0u32).to_vec();
+    expected.extend_from_slice(&default_bytes!(10u32));
+    expected.extend_from_slice(&default_bytes!(20u32));
+    assert_eq!(v, expected);
+
+    let events = vec![
+        Event::Click { x: 10, y: 20 },
+        Event::Key { code: 13, name: "enter".to_owned(), modifiers: vec![1, 2] },
+        Event::Quit,
+    ];
+    let v = to_vec(&events).unwrap();
+    let r = Vec::<Event>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, events);
+}
+
+mod v1 {
+    use serde_derive::Serialize;
+
+    #[derive(Serialize)]
+    pub enum Event {
+        Start,
+        Move(i32, i32),
+        Stop { code: u8 },
+        Crash,
+    }
+}
+
+mod v2 {
+    use serde_derive::Deserialize;
+
+    #[derive(Deserialize, Eq, PartialEq, Debug)]
+    pub enum Event {
+        Pause,
+        Stop { code: u8 },
+        Start,
+        Move(i32, i32),
+    }
+}
+
+#[test]
+fn test_variant_name_hash() {
+    use byteorder::NativeEndian;
+    use tirse::{BinarySerializer, BinaryDeserializer, NameHashDelegate, name_hash};
+    use tirse::{DefaultBinarySerializerDelegate, DefaultBinaryDeserializerDelegate};
+
+    type HashSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, NativeEndian, NameHashDelegate<DefaultBinarySerializerDelegate>, String>;
+    type HashDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, NativeEndian, NameHashDelegate<DefaultBinaryDeserializerDelegate>, String>;
+
+    let v = to_vec!(HashSerializer, &v1::Event::Start);
+    assert_eq!(v, name_hash("Start").to_ne_bytes());
+    assert_eq!(v2::Event::deserialize(HashDeserializer::new(v.iter())).unwrap(), v2::Event::Start);
+
+    let v = to_vec!(HashSerializer, &v1::Event::Move(-1, 2));
+    assert_eq!(v2::Event::deserialize(HashDeserializer::new(v.iter())).unwrap(), v2::Event::Move(-1, 2));
+
+    let v = to_vec!(HashSerializer, &v1::Event::Stop { code: 3 });
+    assert_eq!(v2::Event::deserialize(HashDeserializer::new(v.iter())).unwrap(), v2::Event::Stop { code: 3 });
+
+    let v = to_vec!(HashSerializer, &v1::Event::Crash);
+    match v2::Event::deserialize(HashDeserializer::new(v.iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(tag)))) => {
+            assert_eq!(tag, name_hash("Crash"))
+        },
+        r => panic!("expected unexpected variant, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_variant_name_hash_over_compact_variants() {
+    use byteorder::LittleEndian;
+    use tirse::{BinarySerializer, BinaryDeserializer, NameHashDelegate, CompactVariantDelegate, name_hash};
+
+    type HashSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, NameHashDelegate<CompactVariantDelegate>, String>;
+    type HashDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, NameHashDelegate<CompactVariantDelegate>, String>;
+
+    let v = v1::Event::Stop { code: 3 }.serialize(HashSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    let mut expected = name_hash("Stop").to_le_bytes().to_vec();
+    expected.push(3);
+    assert_eq!(v, expected);
+    assert_eq!(v2::Event::deserialize(HashDeserializer::new(v.iter())).unwrap(), v2::Event::Stop { code: 3 });
+
+    let v = Some(7u8).serialize(HashSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, [1, 0, 0, 0, 7]);
+    assert_eq!(Option::<u8>::deserialize(HashDeserializer::new(v.iter())).unwrap(), Some(7));
+}
+
+#[test]
+fn test_single_byte_variant_delegate() {
+    use byteorder::{ByteOrder, LittleEndian};
+    use tirse::{
+        BinarySerializer, BinaryDeserializer, BinarySerializerDelegate, BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+    };
+
+    pub struct ByteVariantDelegate;
+
+    impl BinarySerializerDelegate for ByteVariantDelegate {
+        type Variant = u8;
+        type Length = usize;
+        type SequenceLength = usize;
+        type Char = u32;
+
+        fn encode_variant(v: u32) -> Self::Variant {
+            v as _
+        }
+
+        fn encode_length(v: usize) -> Self::Length {
+            v
+        }
+
+        fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+            v
+        }
+
+        fn encode_char(v: char) -> Self::Char {
+            v as _
+        }
+    }
+
+    impl BinaryDeserializerDelegate for ByteVariantDelegate {
+        type SmallBuffer = [u8; 8];
+
+        fn variant_size() -> usize {
+            1
+        }
+
+        fn length_size() -> usize {
+            DefaultBinaryDeserializerDelegate::length_size()
+        }
+
+        fn sequence_length_size() -> usize {
+            DefaultBinaryDeserializerDelegate::sequence_length_size()
+        }
+
+        fn char_size() -> usize {
+            DefaultBinaryDeserializerDelegate::char_size()
+        }
+
+        fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+            u32::from(bytes[0])
+        }
+
+        fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+        }
+
+        fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+        }
+
+        fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+        }
+    }
+
+    type ByteSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, ByteVariantDelegate, String>;
+    type ByteDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, ByteVariantDelegate, String>;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    pub enum Message {
+        Ping,
+        Move(i16, i16),
+        Say { text: String },
+        Color(Color),
+    }
+
+    let value = vec![
+        Message::Ping,
+        Message::Move(-1, 2),
+        Message::Say { text: "hi".to_owned() },
+        Message::Color(Color::Blue),
+        Message::Ping,
+    ];
+    let v = value.serialize(ByteSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(&v[8..9], &[0]);
+    assert_eq!(&v[9..14], &[1, 0xff, 0xff, 2, 0]);
+    assert_eq!(v[v.len() - 3..], [3, 2, 0]);
+
+    let r = Vec::<Message>::deserialize(ByteDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+}
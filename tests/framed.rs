@@ -0,0 +1,203 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::io::Cursor;
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::FramedWrite;
+use tirse::FramedRead;
+use tirse::ReadWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+type DeserializeFromIter<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut Iter<'a, u8>, String>;
+type FramedSerializer<'a, 'b> = DefaultBinarySerializer<&'b mut FramedWrite<&'a mut Vec<u8>>, String>;
+type FramedDeserializer<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut FramedRead<'a, ReadWrapper<Cursor<Vec<u8>>>>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Message {
+    Hello { name: String },
+    Data(Vec<u16>),
+    Bye,
+}
+
+#[test]
+fn test_three_messages() {
+    let messages = vec![
+        Message::Hello { name: "node".to_owned() },
+        Message::Data(vec![1, 2, 3]),
+        Message::Bye,
+    ];
+
+    let mut stream = Vec::new();
+    let mut w = FramedWrite::new(&mut stream);
+    for message in &messages {
+        message.serialize(FramedSerializer::new(&mut w)).unwrap();
+        w.finalize().unwrap();
+    }
+    assert_eq!(stream[..4], 16u32.to_le_bytes());
+    assert_eq!(stream[stream.len() - 8..], [4u32.to_le_bytes(), default_bytes!(2u32)].concat());
+
+    let mut r = FramedRead::new(ReadWrapper::from(Cursor::new(stream.clone())));
+    for message in &messages {
+        assert_eq!(&Message::deserialize(FramedDeserializer::new(&mut r)).unwrap(), message);
+        r.next_frame().unwrap();
+    }
+
+    // a shorter read leaves the rest of the frame, a longer one cannot reach the next frame
+    let mut r = FramedRead::new(ReadWrapper::from(Cursor::new(stream)));
+    assert_eq!(u32::deserialize(FramedDeserializer::new(&mut r)).unwrap(), 0);
+    r.next_frame().unwrap();
+    assert_eq!(<(u32, Vec<u16>)>::deserialize(FramedDeserializer::new(&mut r)).unwrap(), (1, vec![1, 2, 3]));
+    r.next_frame().unwrap();
+    assert!(<(u32, u32)>::deserialize(FramedDeserializer::new(&mut r)).is_err());
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Extension {
+    id: u16,
+    payload: String,
+}
+
+#[test]
+fn test_skip_framed() {
+    let extension = Extension { id: 7, payload: "unknown to the reader".to_owned() };
+
+    let s = SerializerIntoVec::new(Vec::new());
+    let s = 0xaau8.serialize(s).unwrap();
+    let s = s.serialize_framed(&extension).unwrap();
+    let v = 0xbbu8.serialize(s).unwrap().consume().into_inner();
+
+    let mut iter = v.iter();
+    assert_eq!(u8::deserialize(DeserializeFromIter::new(&mut iter)).unwrap(), 0xaa);
+    let length = usize::deserialize(DeserializeFromIter::new(&mut iter)).unwrap();
+    let body = &iter.as_slice()[..length];
+    iter.nth(length - 1);
+    assert_eq!(u8::deserialize(DeserializeFromIter::new(&mut iter)).unwrap(), 0xbb);
+
+    let r = Extension::deserialize(DeserializeFromSlice::new(body.iter())).unwrap();
+    assert_eq!(r, extension);
+}
+
+#[test]
+fn test_read_nested() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    pub struct Bigger {
+        id: u16,
+        payload: String,
+        more: u64,
+    }
+
+    let extension = Extension { id: 7, payload: "nested".to_owned() };
+
+    let s = SerializerIntoVec::new(Vec::new());
+    let s = s.serialize_framed(&extension).unwrap();
+    let s = s.serialize_framed(&extension).unwrap();
+    let v = 0xbbu8.serialize(s).unwrap().consume().into_inner();
+
+    let mut d = DeserializeFromSlice::new(v.iter());
+    let nested = d.read_nested().unwrap();
+    assert!(Bigger::deserialize(nested).is_err());
+    let nested = d.read_nested().unwrap();
+    assert_eq!(Extension::deserialize(nested).unwrap(), extension);
+    assert_eq!(u8::deserialize(d).unwrap(), 0xbb);
+}
+
+#[test]
+fn test_length_prefixed_bytes() {
+    let blob = [0xde, 0xad, 0xbe, 0xef, 0x00];
+
+    let s = SerializerIntoVec::new(Vec::new());
+    let s = s.serialize_length_prefixed_bytes(&blob).unwrap();
+    let v = 0xbbu8.serialize(s).unwrap().consume().into_inner();
+    assert_eq!(v.len(), std::mem::size_of::<usize>() + blob.len() + 1);
+
+    let mut iter = v.iter();
+    let r = <&[u8]>::deserialize(DeserializeFromIter::new(&mut iter)).unwrap();
+    assert_eq!(r, &blob[..]);
+    assert_eq!(u8::deserialize(DeserializeFromIter::new(&mut iter)).unwrap(), 0xbb);
+}
+
+#[test]
+fn test_raw_write_magic() {
+    const MAGIC: [u8; 4] = *b"TIRS";
+
+    let extension = Extension { id: 3, payload: "after the magic".to_owned() };
+
+    let mut s = SerializerIntoVec::new(Vec::new());
+    s.raw_write(&MAGIC).unwrap();
+    let v = extension.serialize(s).unwrap().consume().into_inner();
+    assert_eq!(v[..4], MAGIC);
+
+    let r = Extension::deserialize(DeserializeFromSlice::new(v[4..].iter())).unwrap();
+    assert_eq!(r, extension);
+
+    // a generic `Serialize` cannot reach `raw_write`, it writes the magic as an array
+    pub struct WithMagic<'a>(&'a Extension);
+
+    impl Serialize for WithMagic<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            (MAGIC, self.0).serialize(serializer)
+        }
+    }
+
+    let w = WithMagic(&extension).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(w, v);
+}
+
+#[test]
+fn test_read_exact_elements() {
+    #[derive(Serialize)]
+    pub struct Header {
+        magic: u16,
+        count: u8,
+    }
+
+    let header = Header { magic: 0xcafe, count: 3 };
+    let s = header.serialize(SerializerIntoVec::new(Vec::new())).unwrap();
+    let s = (10u32, 20u32, 30u32).serialize(s).unwrap();
+    let v = 0xbbu8.serialize(s).unwrap().consume().into_inner();
+
+    let mut iter = v.iter();
+    let mut d = DeserializeFromIter::new(&mut iter);
+    let (magic, count) = <(u16, u8)>::deserialize(d.split()).unwrap();
+    assert_eq!(magic, 0xcafe);
+    let r = d.read_exact_elements::<u32>(usize::from(count)).unwrap();
+    assert_eq!(r, [10, 20, 30]);
+    assert_eq!(u8::deserialize(d).unwrap(), 0xbb);
+
+    let mut iter = v[3..10].iter();
+    assert!(DeserializeFromIter::new(&mut iter).read_exact_elements::<u32>(3).is_err());
+}
+
+#[test]
+fn test_write_elements() {
+    let items = [Extension { id: 1, payload: "a".to_owned() }, Extension { id: 2, payload: "bc".to_owned() }];
+
+    let mut s = (items.len() as u8).serialize(SerializerIntoVec::new(Vec::new())).unwrap();
+    s.write_elements(&items).unwrap();
+    s.write_elements::<u8>(&[]).unwrap();
+    let v = s.consume().into_inner();
+    assert_eq!(v.len(), 1 + (2 + 8 + 1) + (2 + 8 + 2));
+
+    let mut iter = v.iter();
+    let mut d = DeserializeFromIter::new(&mut iter);
+    let count = u8::deserialize(d.split()).unwrap();
+    let r = d.read_exact_elements::<Extension>(usize::from(count)).unwrap();
+    assert_eq!(r, items);
+    assert!(iter.as_slice().is_empty());
+}
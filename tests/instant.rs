@@ -0,0 +1,47 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+use std::time::{Duration, Instant};
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+use tirse::instant_as_duration;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Sample {
+    #[serde(with = "instant_as_duration")]
+    at: Instant,
+    value: u32,
+}
+
+#[test]
+fn test_instant_round_trip() {
+    let reference = Instant::now();
+    let sample = Sample { at: reference + Duration::new(3, 500), value: 7 };
+
+    let v = instant_as_duration::with_reference(reference, || {
+        sample.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner()
+    });
+    let expected = (Duration::new(3, 500), 7u32)
+        .serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, expected);
+
+    let r = instant_as_duration::with_reference(reference, || {
+        Sample::deserialize(DeserializeFromSlice::new(v.iter())).unwrap()
+    });
+    assert_eq!(r, sample);
+
+    assert!(Sample::deserialize(DeserializeFromSlice::new(v.iter())).is_err());
+    assert!(instant_as_duration::with_reference(sample.at + Duration::new(1, 0), || {
+        sample.serialize(SerializerIntoVec::new(Vec::new())).is_err()
+    }));
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::DefaultEndian;
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinarySerializer;
+use tirse::InternedStringDelegate;
+use tirse::PrimitiveTagDelegate;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type InternedSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, DefaultEndian, InternedStringDelegate<DefaultBinarySerializerDelegate>, String>;
+type InternedDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, DefaultEndian, InternedStringDelegate<DefaultBinaryDeserializerDelegate>, String>;
+
+#[test]
+fn test_interned_strings() {
+    let columns = ["id", "name", "id", "id", "name", "timestamp", "name"]
+        .iter()
+        .map(|&s| s.to_owned())
+        .collect::<Vec<_>>();
+
+    let naive = columns.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let v = columns.serialize(InternedSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert!(v.len() < naive.len());
+
+    // the third string refers to the first one
+    assert_eq!(v[8..12], default_bytes!(0u32));
+    assert_eq!(v[38..42], default_bytes!(1u32));
+
+    let r = Vec::<String>::deserialize(InternedDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, columns);
+}
+
+#[test]
+fn test_interned_nested() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub enum Cell {
+        Text(String),
+        Number(u32),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct Row {
+        column: String,
+        cells: Vec<Cell>,
+    }
+
+    let rows = vec![
+        Row { column: "a".to_owned(), cells: vec![Cell::Text("x".to_owned()), Cell::Number(1)] },
+        Row { column: "x".to_owned(), cells: vec![Cell::Text("a".to_owned()), Cell::Text("x".to_owned())] },
+    ];
+
+    let v = rows.serialize(InternedSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    let r = Vec::<Row>::deserialize(InternedDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, rows);
+
+    // the interned strings inside the tagged primitives
+    type Serializer = BinarySerializer<WriteWrapper<Vec<u8>>, DefaultEndian, PrimitiveTagDelegate<InternedStringDelegate<DefaultBinarySerializerDelegate>>, String>;
+    type Deserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, DefaultEndian, PrimitiveTagDelegate<InternedStringDelegate<DefaultBinaryDeserializerDelegate>>, String>;
+
+    let v = rows.serialize(Serializer::new(Vec::new())).unwrap().consume().into_inner();
+    let r = Vec::<Row>::deserialize(Deserializer::new(v.iter())).unwrap();
+    assert_eq!(r, rows);
+}
+
+#[test]
+fn test_unknown_interned_string() {
+    let v = default_bytes!(3u32);
+    match String::deserialize(InternedDeserializer::new(v.iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnknownString(3)))) => (),
+        r => panic!("expected unknown string, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_try_deserialize_forgets_strings() {
+    use std::num::NonZeroU32;
+
+    let value = ("x".to_owned(), "y".to_owned(), "y".to_owned());
+    let v = value.serialize(InternedSerializer::new(Vec::new())).unwrap().consume().into_inner();
+
+    // the failed attempt reads "x" and fails on the tag of "y", which is zero
+    let mut d = InternedDeserializer::new(v.iter());
+    assert!(d.try_deserialize::<(String, NonZeroU32)>().is_err());
+    // "x" is interned again at the same index, so the last string still refers to "y"
+    let r = d.try_deserialize::<(String, String, String)>().unwrap();
+    assert_eq!(r, value);
+}
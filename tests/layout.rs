@@ -0,0 +1,50 @@
+#![cfg(feature = "use_std")]
+
+use core::num::NonZeroU16;
+
+use serde_derive::Serialize;
+
+use tirse::layout_of;
+use tirse::FieldLayout;
+
+#[derive(Serialize)]
+pub struct Header {
+    magic: [u8; 4],
+    version: NonZeroU16,
+    flags: u8,
+    length: u32,
+    checksum: u64,
+    ratio: f32,
+    kind: Kind,
+}
+
+#[derive(Serialize)]
+pub enum Kind {
+    Data = 1,
+}
+
+#[test]
+fn test_layout_of() {
+    let header = Header {
+        magic: *b"TIRS",
+        version: NonZeroU16::new(1).unwrap(),
+        flags: 0,
+        length: 0,
+        checksum: 0,
+        ratio: 0.0,
+        kind: Kind::Data,
+    };
+    let layout = layout_of(&header).unwrap();
+    let expected = [
+        FieldLayout { name: "magic", offset: 0, size: 4 },
+        FieldLayout { name: "version", offset: 4, size: 2 },
+        FieldLayout { name: "flags", offset: 6, size: 1 },
+        FieldLayout { name: "length", offset: 7, size: 4 },
+        FieldLayout { name: "checksum", offset: 11, size: 8 },
+        FieldLayout { name: "ratio", offset: 19, size: 4 },
+        FieldLayout { name: "kind", offset: 23, size: 4 },
+    ];
+    assert_eq!(layout, expected);
+
+    assert!(layout_of(&7u32).is_err());
+}
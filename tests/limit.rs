@@ -0,0 +1,55 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Serializer;
+
+use std::cell::Cell;
+
+use tirse::Write;
+use tirse::LimitWrite;
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializerError;
+use tirse::to_vec_limited;
+
+pub struct Counted<'a> {
+    value: u32,
+    serialized: &'a Cell<usize>,
+}
+
+impl<'a> Serialize for Counted<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serialized.set(self.serialized.get() + 1);
+        self.value.serialize(serializer)
+    }
+}
+
+#[test]
+fn test_to_vec_limited() {
+    let serialized = Cell::new(0);
+    let values = (0..1000).map(|value| Counted { value, serialized: &serialized }).collect::<Vec<_>>();
+
+    // the length and ten elements fit
+    match to_vec_limited::<_, String>(8 + 10 * 4 + 3, &values) {
+        Err(ErrorAdapter::Inner(BinarySerializerError::SizeLimitExceeded)) => (),
+        Err(e) => panic!("expected size limit exceeded, got {:?}", e),
+        Ok(_) => panic!("expected size limit exceeded"),
+    }
+    assert_eq!(serialized.get(), 11);
+
+    let v = to_vec_limited::<_, String>(8 + 1000 * 4, &values).unwrap();
+    assert_eq!(v.len(), 8 + 1000 * 4);
+}
+
+#[test]
+fn test_limit_write() {
+    let mut write = LimitWrite::new(WriteWrapper::from(Vec::new()), 4);
+    write.write(&[1, 2, 3]).unwrap();
+    assert!(write.write(&[4, 5]).is_err());
+    assert_eq!(write.limit(), 1);
+    write.write(&[4]).unwrap();
+    assert_eq!(write.into_inner().into_inner(), [1, 2, 3, 4]);
+}
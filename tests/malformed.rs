@@ -0,0 +1,260 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+
+use std::collections::BTreeMap;
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::Read;
+use tirse::ErrorAdapter;
+use tirse::BinaryDeserializerError;
+use tirse::Write;
+use tirse::Cursor;
+use tirse::ReadWrapper;
+use tirse::DefaultBinaryDeserializer;
+use tirse::DefaultBinarySerializer;
+use tirse::WriteWrapper;
+use tirse::END_MARKER;
+
+struct ShortRead<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Read<'a> for ShortRead<'a> {
+    type Error = std::io::Error;
+
+    fn read(&mut self, length: usize) -> Option<Result<&'a [u8], Self::Error>> {
+        // misbehaving reader, returns one byte less than requested
+        let (s, rest) = self.data.split_at(length.saturating_sub(1).min(self.data.len()));
+        self.data = rest;
+        Some(Ok(s))
+    }
+
+    fn read_in_buffer<B>(&mut self, buffer: &mut B, length: usize) -> Result<(), Self::Error>
+    where
+        B: AsMut<[u8]>,
+    {
+        let _ = (buffer, length);
+        Err(std::io::Error::other("not used"))
+    }
+
+    fn is(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+
+#[test]
+fn test_empty_read() {
+    let v = [];
+    let mut iter = v.iter();
+    assert_eq!(iter.read(0).unwrap().unwrap(), &[] as &[u8]);
+    assert!(Vec::<u8>::deserialize(DeserializeFromSlice::new(v.iter())).is_err());
+}
+
+#[test]
+fn test_map_key_without_value() {
+    let mut v = default_bytes!(2usize).to_vec();
+    v.push(1);
+    assert!(BTreeMap::<u8, u8>::deserialize(DeserializeFromSlice::new(v.iter())).is_err());
+}
+
+#[test]
+fn test_short_read() {
+    let v = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let r = u8::deserialize(DefaultBinaryDeserializer::<_, String>::new(ShortRead { data: &v }));
+    assert!(matches!(r, Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedEof)))));
+    let r = bool::deserialize(DefaultBinaryDeserializer::<_, String>::new(ShortRead { data: &v }));
+    assert!(matches!(r, Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedEof)))));
+    let r = u32::deserialize(DefaultBinaryDeserializer::<_, String>::new(ShortRead { data: &v }));
+    assert!(matches!(r, Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedEof)))));
+    let r = char::deserialize(DefaultBinaryDeserializer::<_, String>::new(ShortRead { data: &v }));
+    assert!(matches!(r, Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedEof)))));
+}
+
+#[test]
+fn test_read_in_small_buffer() {
+    let v = [1, 2, 3, 4];
+    let mut buffer = [0; 2];
+
+    let mut iter = v.iter();
+    assert!(iter.read_in_buffer(&mut buffer, 4).is_err());
+    assert_eq!(iter.as_slice().len(), 4);
+
+    let mut cursor = Cursor::new(&v[..]);
+    assert!(cursor.read_in_buffer(&mut buffer, 4).is_err());
+    assert_eq!(cursor.position(), 0);
+
+    let mut wrapper = ReadWrapper::from(std::io::Cursor::new(v.to_vec()));
+    assert!(wrapper.read_in_buffer(&mut buffer, 4).is_err());
+}
+
+#[test]
+fn test_cursor_past_the_end() {
+    let v = [1, 2];
+    let mut cursor = Cursor::new(&v[..]);
+    cursor.set_position(5);
+    assert!(cursor.read(1).unwrap().is_err());
+    assert!(cursor.read(0).unwrap().is_ok());
+
+    let mut buffer = [0; 2];
+    let mut cursor = Cursor::new(&mut buffer[..]);
+    cursor.set_position(5);
+    assert!(cursor.write(&[]).is_ok());
+    assert!(cursor.write(&[1]).is_err());
+}
+
+#[test]
+fn test_short_write_into_slice() {
+    let mut buffer = [0; 4];
+    let mut iter = buffer.iter_mut();
+    iter.write(&[1, 2]).unwrap();
+    iter.write(&[3]).unwrap();
+    assert!(iter.write(&[4, 5]).is_err());
+    assert_eq!(buffer, [1, 2, 3, 0]);
+}
+
+#[test]
+fn test_io_error_in_set() {
+    use std::collections::HashSet;
+
+    let v = [1, 2];
+    let mut errors = HashSet::new();
+    for length in [3, 4, 3] {
+        let mut cursor = Cursor::new(&v[..]);
+        errors.insert(cursor.read(length).unwrap().unwrap_err());
+    }
+    assert_eq!(errors.len(), 2);
+
+    let mut cursor = Cursor::new(&v[..]);
+    let e = cursor.read(3).unwrap().unwrap_err();
+    assert!(errors.contains(&e.clone()));
+}
+
+#[test]
+fn test_end_marker() {
+    let value = (7u32, "complete".to_owned());
+    let v = SerializerIntoVec::new(Vec::new())
+        .serialize_with_end_marker(&value)
+        .unwrap()
+        .consume()
+        .into_inner();
+    assert_eq!(v[v.len() - END_MARKER.len()..], END_MARKER);
+
+    let r = DeserializeFromSlice::new(v.iter()).deserialize_with_end_marker::<(u32, String)>();
+    assert_eq!(r.unwrap(), value);
+
+    // the value is intact, but the stream is cut before or inside the marker
+    for cut in 1..=END_MARKER.len() {
+        let truncated = &v[..v.len() - cut];
+        match DeserializeFromSlice::new(truncated.iter()).deserialize_with_end_marker::<(u32, String)>() {
+            Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::MissingEndMarker))) => (),
+            r => panic!("expected missing end marker, got {:?}", r),
+        }
+    }
+}
+
+#[test]
+fn test_invalid_bool() {
+    assert!(!bool::deserialize(DeserializeFromSlice::new([0].iter())).unwrap());
+    assert!(bool::deserialize(DeserializeFromSlice::new([1].iter())).unwrap());
+    match bool::deserialize(DeserializeFromSlice::new([2].iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::InvalidBool(2)))) => (),
+        r => panic!("expected invalid bool, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_length_too_large() {
+    use std::io::Cursor;
+
+    type DeserializeFromRead = DefaultBinaryDeserializer<'static, ReadWrapper<Cursor<Vec<u8>>>, String>;
+
+    // a terabyte would abort the process if the buffer was allocated
+    let mut v = default_bytes!(1u64 << 40).to_vec();
+    v.extend_from_slice(b"tail");
+    match String::deserialize(DeserializeFromRead::new(ReadWrapper::from(Cursor::new(v.clone())))) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::LengthTooLarge { requested, limit }))) => {
+            assert_eq!(requested, 1 << 40);
+            assert_eq!(limit, 16 * 1024 * 1024);
+        },
+        r => panic!("expected length too large, got {:?}", r),
+    }
+
+    struct Buffer;
+
+    impl<'de> serde::de::Visitor<'de> for Buffer {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "bytes")
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+    }
+
+    let r = serde::Deserializer::deserialize_byte_buf(DeserializeFromRead::new(ReadWrapper::from(Cursor::new(v))), Buffer);
+    match r {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::LengthTooLarge { .. }))) => (),
+        r => panic!("expected length too large, got {:?}", r),
+    }
+
+    let mut v = default_bytes!(4u64).to_vec();
+    v.extend_from_slice(b"tail");
+    let r = String::deserialize(DeserializeFromSlice::new(v.iter()).with_max_alloc(4)).unwrap();
+    assert_eq!(r, "tail");
+    match String::deserialize(DeserializeFromSlice::new(v.iter()).with_max_alloc(3)) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::LengthTooLarge { requested: 4, limit: 3 }))) => (),
+        r => panic!("expected length too large, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_compare_errors() {
+    use serde::Serialize;
+    use serde_derive::Deserialize;
+    use tirse::{BinarySerializerError, FixedSerializer, FixedBuffer};
+
+    #[derive(Deserialize, Debug)]
+    pub enum Light {
+        Red,
+        Green,
+    }
+
+    let r = Light::deserialize(DeserializeFromSlice::new(default_bytes!(7u32).iter())).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(7))));
+
+    let r = bool::deserialize(DeserializeFromSlice::new([2].iter())).unwrap_err();
+    assert_ne!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::InvalidBool(3))));
+
+    let r = vec![[[0u8; 1]; 1]; 1]
+        .serialize(FixedSerializer::<64, String>::new(FixedBuffer::new()).with_max_depth(2))
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinarySerializerError::DepthLimitExceeded)));
+}
+
+#[test]
+fn test_trailing_bytes() {
+    use tirse::from_slice;
+
+    let r = from_slice::<u32>(&[1, 0, 0, 0, 99]).unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::TrailingBytes(1))));
+    assert!(from_slice::<u32>(&[1, 0, 0, 0]).is_ok());
+
+    // a deserializer checks it on request
+    let v = [1, 0, 0, 0, 99, 98];
+    let mut d = DeserializeFromSlice::new(v.iter());
+    u32::deserialize(d.split()).unwrap();
+    assert_eq!(d.finish().unwrap_err(), ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::TrailingBytes(2))));
+}
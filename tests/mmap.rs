@@ -0,0 +1,47 @@
+#![cfg(feature = "use_std")]
+
+// a memory map derefs to a slice, so the slice reader borrows from the mapped file
+// and the borrow checker ties the deserialized fields to the lifetime of the map
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::{fs, slice::Iter};
+
+use memmap2::Mmap;
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Entry<'a> {
+    id: u32,
+    name: &'a str,
+    data: &'a [u8],
+}
+
+#[test]
+fn test_borrow_from_mmap() {
+    let entry = Entry { id: 42, name: "mapped", data: &[1, 2, 3, 4] };
+    let v = entry.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let path = std::env::temp_dir().join(format!("tirse-mmap-{}", std::process::id()));
+    fs::write(&path, &v).unwrap();
+    let file = fs::File::open(&path).unwrap();
+    // the mapping outlives the borrowed fields, the file is not modified while mapped
+    let map = unsafe { Mmap::map(&file) }.unwrap();
+
+    let r = Entry::deserialize(DeserializeFromSlice::new(map.iter())).unwrap();
+    assert_eq!(r, entry);
+    assert!(map.as_ptr_range().contains(&r.name.as_ptr()));
+    assert!(map.as_ptr_range().contains(&r.data.as_ptr()));
+
+    drop(map);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Serializer;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use either::Either;
+
+use tirse::net;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializerError;
+use tirse::BinaryDeserializerError;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Kind {
+    Ping,
+    Data(u16),
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Message<'a> {
+    id: u32,
+    kind: Kind,
+    name: &'a str,
+    tags: Vec<u8>,
+    reply: Option<u16>,
+}
+
+#[test]
+fn test_network_byte_order() {
+    let message = Message {
+        id: 0x01020304,
+        kind: Kind::Data(0x0a0b),
+        name: "hi",
+        tags: vec![7, 8],
+        reply: None,
+    };
+    let v = net::to_vec(&message).unwrap();
+    assert_eq!(v, [
+        0x01, 0x02, 0x03, 0x04,
+        0x00, 0x00, 0x00, 0x01, 0x0a, 0x0b,
+        0x00, 0x00, 0x00, 0x02, b'h', b'i',
+        0x00, 0x00, 0x00, 0x02, 7, 8,
+        0x00, 0x00, 0x00, 0x00,
+    ]);
+
+    let r = net::from_slice::<Message>(&v).unwrap();
+    assert_eq!(r, message);
+    assert!(net::from_slice::<Message>(&v[..v.len() - 1]).is_err());
+}
+
+#[test]
+fn test_network_errors() {
+    let v = net::to_vec(&0x0102u16).unwrap();
+
+    // the input must end with the value, as with `tirse::from_slice`
+    let mut trailing = v.clone();
+    trailing.push(0);
+    match net::from_slice::<u16>(&trailing) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::TrailingBytes(1)))) => (),
+        r => panic!("expected one trailing byte, got {:?}", r),
+    }
+
+    match net::from_slice::<u16>(&v[..1]) {
+        Err(ErrorAdapter::Inner(Either::Right(_))) => (),
+        r => panic!("expected the end of the input, got {:?}", r),
+    }
+
+    struct Unknown;
+
+    impl Serialize for Unknown {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_seq((0u8..3).filter(|&x| x != 1))
+        }
+    }
+
+    // the serializer error keeps its variant
+    match net::to_vec(&Unknown) {
+        Err(ErrorAdapter::Inner(BinarySerializerError::UnknownLength)) => (),
+        r => panic!("expected unknown length, got {:?}", r),
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::to_vec;
+use tirse::OffsetRead;
+use tirse::ErrorAdapter;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinaryDeserializer;
+
+type DeserializeAt<'a> = DefaultBinaryDeserializer<'a, OffsetRead<Iter<'a, u8>>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub enum Kind {
+    File,
+    Directory,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Entry {
+    size: u64,
+    mode: u32,
+    kind: Kind,
+    hidden: bool,
+}
+
+#[test]
+fn test_position() {
+    let entry = Entry { size: 1, mode: 0o644, kind: Kind::Directory, hidden: false };
+    let v = to_vec(&(&entry, 9u8)).unwrap();
+
+    let mut d = DeserializeAt::new(OffsetRead::new(v.iter()));
+    assert_eq!(d.deserialize_at::<Entry>().unwrap(), entry);
+    assert_eq!(d.position(), 17);
+    assert_eq!(d.deserialize_at::<u8>().unwrap(), 9);
+    assert_eq!(d.position(), 18);
+}
+
+#[test]
+fn test_bad_variant_offset() {
+    let mut v = to_vec(&Entry { size: 1, mode: 0o644, kind: Kind::File, hidden: true }).unwrap();
+    v[12..16].copy_from_slice(&default_bytes!(7u32));
+
+    let e = DeserializeAt::new(OffsetRead::new(v.iter())).deserialize_at::<Entry>().unwrap_err();
+    assert_eq!(e.offset, 12);
+    assert!(matches!(e.error, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedVariant(7)))));
+
+    v[12..16].copy_from_slice(&default_bytes!(1u32));
+    v[16] = 2;
+    let e = DeserializeAt::new(OffsetRead::new(v.iter())).deserialize_at::<Entry>().unwrap_err();
+    assert_eq!(e.offset, 16);
+    assert!(matches!(e.error, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::InvalidBool(2)))));
+    assert!(e.to_string().starts_with("at offset 16: "));
+
+    let e = DeserializeAt::new(OffsetRead::new(v[..14].iter())).deserialize_at::<Entry>().unwrap_err();
+    assert_eq!(e.offset, 12);
+    assert!(matches!(e.error, ErrorAdapter::Inner(Either::Right(_))));
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use tirse::PatchWrite;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+use tirse::BinarySerializerError;
+use tirse::ErrorAdapter;
+
+use either::Either;
+
+type SerializerIntoPatch = DefaultBinarySerializer<PatchWrite, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Body {
+    name: String,
+    values: Vec<u16>,
+}
+
+#[test]
+fn test_patch_placeholder_length() {
+    let body = Body { name: "header".to_owned(), values: vec![1, 2, 3] };
+
+    let mut s = SerializerIntoPatch::new(Vec::new());
+    s.mark("length");
+    let s = 0u32.serialize(s).unwrap();
+    let start = s.position();
+    let mut s = body.serialize(s).unwrap();
+    let length = (s.position() - start) as u32;
+    s.patch("length", &length).unwrap();
+    let v = s.consume().into_inner();
+
+    let (l, r) = <(u32, Body)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(l as usize, v.len() - 4);
+    assert_eq!(r, body);
+}
+
+#[test]
+fn test_patch_errors() {
+    let mut s = SerializerIntoPatch::new(Vec::new());
+    s.mark("tail");
+    let mut s = 7u16.serialize(s).unwrap();
+
+    match s.patch("head", &0u16) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownMark))) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+    assert!(s.patch("tail", &0u32).is_err());
+    s.patch("tail", &9u16).unwrap();
+    assert_eq!(s.consume().into_inner(), default_bytes!(9u16));
+}
+
+#[test]
+fn test_patch_interned_string() {
+    use tirse::{BinarySerializer, DefaultEndian, InternedStringDelegate, DefaultBinarySerializerDelegate};
+
+    type InternedIntoPatch = BinarySerializer<PatchWrite, DefaultEndian, InternedStringDelegate<DefaultBinarySerializerDelegate>, String>;
+
+    // the placeholder is the first string the reader sees, the patch cannot renumber it
+    let mut s = InternedIntoPatch::new(Vec::new());
+    s.mark("name");
+    let s = "????".serialize(s).unwrap();
+    let mut s = "name".serialize(s).unwrap();
+    match s.patch("name", "name") {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::PatchInterned))) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+}
@@ -0,0 +1,84 @@
+#![cfg(all(feature = "use_std", feature = "bytemuck"))]
+
+#[macro_use]
+mod common;
+
+use std::borrow::Cow;
+use std::slice::Iter;
+
+use byteorder::{LittleEndian, BigEndian};
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+
+#[cfg(target_endian = "little")]
+type Native = LittleEndian;
+#[cfg(target_endian = "little")]
+type Foreign = BigEndian;
+#[cfg(target_endian = "big")]
+type Native = BigEndian;
+#[cfg(target_endian = "big")]
+type Foreign = LittleEndian;
+
+type SerializerIntoVec<E> = BinarySerializer<WriteWrapper<Vec<u8>>, E, DefaultBinarySerializerDelegate, String>;
+type DeserializeFromSlice<'a, E> = BinaryDeserializer<'a, Iter<'a, u8>, E, DefaultBinaryDeserializerDelegate, String>;
+
+const VALUES: [u32; 3] = [1, 2, 0xdead_beef];
+
+#[test]
+fn test_pod_slice() {
+    // the length prefix is eight bytes, so the elements stay aligned after it
+    let v = to_vec!(SerializerIntoVec<Native>, &VALUES[..]);
+    let mut storage = [0u32; 8];
+    let aligned = bytemuck::cast_slice_mut::<u32, u8>(&mut storage);
+    aligned[..v.len()].copy_from_slice(&v);
+
+    let mut d = DeserializeFromSlice::<Native>::new(aligned[..v.len()].iter());
+    match d.read_pod_slice::<u32>().unwrap() {
+        Cow::Borrowed(slice) => {
+            assert_eq!(slice, VALUES);
+            assert_eq!(slice.as_ptr() as *const u8, aligned[8..].as_ptr());
+        },
+        Cow::Owned(_) => panic!("aligned native data must be borrowed"),
+    }
+
+    let mut misaligned = vec![0u8; v.len() + 1];
+    misaligned[1..].copy_from_slice(&v);
+    let mut d = DeserializeFromSlice::<Native>::new(misaligned[1..].iter());
+    match d.read_pod_slice::<u32>().unwrap() {
+        Cow::Owned(vec) => assert_eq!(vec, VALUES),
+        Cow::Borrowed(_) => panic!("misaligned data must be copied"),
+    }
+
+    let v = to_vec!(SerializerIntoVec<Foreign>, &VALUES[..]);
+    let mut d = DeserializeFromSlice::<Foreign>::new(v.iter());
+    match d.read_pod_slice::<u32>().unwrap() {
+        Cow::Owned(vec) => assert_eq!(vec, VALUES),
+        Cow::Borrowed(_) => panic!("foreign byte order must be copied"),
+    }
+
+    let mut d = DeserializeFromSlice::<Native>::new(misaligned[1..misaligned.len() - 1].iter());
+    assert!(d.read_pod_slice::<u32>().is_err());
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn test_pod_slice_length_overflow() {
+    use either::Either;
+    use tirse::{ErrorAdapter, BinaryDeserializerError};
+
+    // the size in bytes of so many elements does not fit in usize
+    let length = u64::MAX / 2;
+    let v = to_vec!(SerializerIntoVec<Native>, &length);
+    let mut d = DeserializeFromSlice::<Native>::new(v.iter());
+    match d.read_pod_slice::<u32>() {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::LengthTooLarge { requested, limit }))) => {
+            assert_eq!(requested, length as usize);
+            assert_eq!(limit, usize::MAX / 4);
+        },
+        r => panic!("expected a too large length, got {:?}", r),
+    }
+}
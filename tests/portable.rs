@@ -0,0 +1,52 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::BinarySerializerDelegate;
+use tirse::PortableSerializerDelegate;
+use tirse::PortableDeserializerDelegate;
+
+type PortableSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, PortableSerializerDelegate, String>;
+type PortableDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, PortableDeserializerDelegate, String>;
+
+#[test]
+fn test_length_is_four_bytes() {
+    assert_eq!(to_vec!(PortableSerializer, "abc"), vec![3, 0, 0, 0, b'a', b'b', b'c']);
+    assert_eq!(to_vec!(PortableSerializer, &vec![1u8, 2]), vec![2, 0, 0, 0, 1, 2]);
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    pub struct Entry {
+        key: String,
+        values: Vec<u16>,
+        extra: Option<Vec<u8>>,
+    }
+
+    let entry = Entry { key: "k".to_owned(), values: vec![1, 2], extra: Some(vec![9]) };
+    let v = to_vec!(PortableSerializer, &entry);
+    assert_eq!(v.len(), 4 + 1 + 4 + 4 + 4 + 4 + 1);
+    let r = Entry::deserialize(PortableDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, entry);
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn test_length_above_u32_is_rejected() {
+    let length = PortableSerializerDelegate::encode_length(u32::MAX as usize);
+    assert_eq!(to_vec!(PortableSerializer, &length), vec![0xff; 4]);
+
+    let length = PortableSerializerDelegate::encode_length(u32::MAX as usize + 1);
+    assert!(length.serialize(PortableSerializer::new(Vec::new())).is_err());
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::BinaryDeserializerError;
+use tirse::ErrorAdapter;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+use tirse::PrefixedVariantDelegate;
+
+type PrefixedSerializer = BinarySerializer<
+    WriteWrapper<Vec<u8>>,
+    LittleEndian,
+    PrefixedVariantDelegate<DefaultBinarySerializerDelegate>,
+    String,
+>;
+type PrefixedDeserializer<'a> = BinaryDeserializer<
+    'a,
+    Iter<'a, u8>,
+    LittleEndian,
+    PrefixedVariantDelegate<DefaultBinaryDeserializerDelegate>,
+    String,
+>;
+
+mod old {
+    use serde_derive::Deserialize;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    pub struct Inner {
+        pub a: u8,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    pub enum Shape {
+        Point { x: u32 },
+        Pair(u16, u16),
+        Nested(Inner),
+        Empty,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    pub struct Message {
+        pub before: u8,
+        pub shape: Shape,
+        pub after: u16,
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Inner {
+    a: u8,
+    b: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub enum Shape {
+    Point { x: u32, y: u32 },
+    Pair(u16, u16, u8),
+    Nested(Inner),
+    Empty,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct Message {
+    before: u8,
+    shape: Shape,
+    after: u16,
+}
+
+#[test]
+fn test_old_reader_skips_new_fields() {
+    let cases = vec![
+        (Shape::Point { x: 1, y: 2 }, old::Shape::Point { x: 1 }),
+        (Shape::Pair(3, 4, 5), old::Shape::Pair(3, 4)),
+        (Shape::Nested(Inner { a: 6, b: 7 }), old::Shape::Nested(old::Inner { a: 6 })),
+        (Shape::Empty, old::Shape::Empty),
+    ];
+
+    for (shape, old_shape) in cases {
+        let message = Message { before: 0xaa, shape, after: 0xbbcc };
+        let v = to_vec!(PrefixedSerializer, &message);
+
+        let r = Message::deserialize(PrefixedDeserializer::new(v.iter())).unwrap();
+        assert_eq!(r, message);
+
+        let r = old::Message::deserialize(PrefixedDeserializer::new(v.iter())).unwrap();
+        assert_eq!(r, old::Message { before: 0xaa, shape: old_shape, after: 0xbbcc });
+    }
+}
+
+#[test]
+fn test_layout() {
+    let v = to_vec!(PrefixedSerializer, &Shape::Pair(3, 4, 5));
+    assert_eq!(v, vec![1, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 3, 0, 4, 0, 5]);
+
+    let v = to_vec!(PrefixedSerializer, &Shape::Empty);
+    assert_eq!(v, vec![3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_payload_cannot_overrun_frame() {
+    let v = vec![1, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 4, 0, 5];
+    let r = Shape::deserialize(PrefixedDeserializer::new(v.iter()));
+    match r {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::FrameOverrun))) => (),
+        r => panic!("unexpected {:?}", r),
+    }
+}
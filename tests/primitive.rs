@@ -0,0 +1,173 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::fmt;
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::BinaryDeserializerError;
+use tirse::PrimitiveTagDelegate;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+
+type TaggedSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, PrimitiveTagDelegate<DefaultBinarySerializerDelegate>, String>;
+type TaggedDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, PrimitiveTagDelegate<DefaultBinaryDeserializerDelegate>, String>;
+
+#[derive(Debug, PartialEq)]
+pub enum Scalar {
+    Bool(bool),
+    I16(i16),
+    U64(u64),
+    F32(f32),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ScalarVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ScalarVisitor {
+            type Value = Scalar;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a scalar")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Scalar, E> {
+                Ok(Scalar::Bool(v))
+            }
+
+            fn visit_i16<E>(self, v: i16) -> Result<Scalar, E> {
+                Ok(Scalar::I16(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Scalar, E> {
+                Ok(Scalar::U64(v))
+            }
+
+            fn visit_f32<E>(self, v: f32) -> Result<Scalar, E> {
+                Ok(Scalar::F32(v))
+            }
+
+            fn visit_char<E>(self, v: char) -> Result<Scalar, E> {
+                Ok(Scalar::Char(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Scalar, E> {
+                Ok(Scalar::Str(v.to_owned()))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Scalar, E> {
+                Ok(Scalar::Bytes(v.to_owned()))
+            }
+        }
+
+        deserializer.deserialize_any(ScalarVisitor)
+    }
+}
+
+fn any(v: &[u8]) -> Scalar {
+    Scalar::deserialize(TaggedDeserializer::new(v.iter())).unwrap()
+}
+
+#[test]
+fn test_deserialize_any_scalar() {
+    assert_eq!(to_vec!(TaggedSerializer, &true), [0x01, 1]);
+    assert_eq!(to_vec!(TaggedSerializer, &-2i16), [0x03, 0xfe, 0xff]);
+
+    assert_eq!(any(&to_vec!(TaggedSerializer, &true)), Scalar::Bool(true));
+    assert_eq!(any(&to_vec!(TaggedSerializer, &-2i16)), Scalar::I16(-2));
+    assert_eq!(any(&to_vec!(TaggedSerializer, &u64::MAX)), Scalar::U64(u64::MAX));
+    assert_eq!(any(&to_vec!(TaggedSerializer, &0.5f32)), Scalar::F32(0.5));
+    assert_eq!(any(&to_vec!(TaggedSerializer, &'ж')), Scalar::Char('ж'));
+    assert_eq!(any(&to_vec!(TaggedSerializer, "text")), Scalar::Str("text".to_owned()));
+    assert_eq!(any(&to_vec!(TaggedSerializer, &Bytes(&[1, 2, 3]))), Scalar::Bytes(vec![1, 2, 3]));
+
+    // the lengths inside are not tagged
+    assert_eq!(to_vec!(TaggedSerializer, "ab"), [0x0d, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+
+    let v = to_vec!(TaggedSerializer, &7u16);
+    match u32::deserialize(TaggedDeserializer::new(v.iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::UnexpectedTag(0x07)))) => (),
+        r => panic!("expected unexpected tag, got {:?}", r),
+    }
+}
+
+pub struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Reading {
+    sensor: Option<u8>,
+    value: Number,
+    kind: Kind,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum Kind {
+    Raw,
+    Scaled(u16),
+}
+
+#[test]
+fn test_schema_around_scalars() {
+    for value in [Number::Int(-3), Number::Float(2.5), Number::Text("n/a".to_owned())] {
+        let reading = Reading { sensor: Some(4), value, kind: Kind::Scaled(10) };
+        let v = to_vec!(TaggedSerializer, &reading);
+        let r = Reading::deserialize(TaggedDeserializer::new(v.iter())).unwrap();
+        assert_eq!(r, reading);
+    }
+}
+
+#[test]
+fn test_not_self_describing() {
+    use tirse::DefaultBinaryDeserializer;
+
+    let v = [1u8, 2];
+    let deserializer = DefaultBinaryDeserializer::<_, String>::new(v.iter());
+    assert!(!deserializer.is_self_describing());
+    match Number::deserialize(deserializer) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing))) => (),
+        r => panic!("expected not self-describing, got {:?}", r),
+    }
+    match serde::de::IgnoredAny::deserialize(DefaultBinaryDeserializer::<_, String>::new(v.iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing))) => (),
+        r => panic!("expected not self-describing, got {:?}", r),
+    }
+
+    assert!(TaggedDeserializer::new(v.iter()).is_self_describing());
+}
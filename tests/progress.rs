@@ -0,0 +1,73 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+
+use either::Either;
+
+use tirse::{DefaultBinarySerializer, DefaultBinaryDeserializer, WriteWrapper};
+use tirse::{ErrorAdapter, BinaryDeserializerError};
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+
+#[test]
+fn test_progress_per_element() {
+    let v = (0..100u32).collect::<Vec<_>>();
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let mut offsets = Vec::new();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .deserialize_with_progress::<Vec<u32>, _>(|offset| offsets.push(offset))
+        .unwrap();
+
+    assert_eq!(r, v);
+    assert_eq!(offsets.len(), 100);
+    assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(offsets.last().cloned(), Some(bytes.len()));
+}
+
+#[test]
+fn test_progress_keeps_settings() {
+    let v = vec!["long enough".to_owned()];
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .with_max_alloc(4)
+        .deserialize_with_progress::<Vec<String>, _>(|_| ())
+        .unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::LengthTooLarge { requested: 11, limit: 4 })));
+
+    let v = vec![vec![1u8]];
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .with_max_depth(1)
+        .deserialize_with_progress::<Vec<Vec<u8>>, _>(|_| ())
+        .unwrap_err();
+    assert_eq!(r, ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::DepthLimitExceeded)));
+
+    let v = [1u16, 2, 3];
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false)).unwrap().consume().into_inner();
+    let mut offsets = Vec::new();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .length_prefixed_sequences(false)
+        .deserialize_with_progress::<Vec<u16>, _>(|offset| offsets.push(offset))
+        .unwrap();
+    assert_eq!(r, v);
+    assert_eq!(offsets, [2, 4, 6]);
+}
+
+#[test]
+fn test_progress_128_bit() {
+    let v = (u128::MAX, -1i128);
+    let bytes = v.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let mut offsets = Vec::new();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .deserialize_with_progress::<(u128, i128), _>(|offset| offsets.push(offset))
+        .unwrap();
+    assert_eq!(r, v);
+    assert_eq!(offsets, [16, 32]);
+
+    let bytes = 7u128.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let r = DefaultBinaryDeserializer::<_, String>::new(bytes.iter())
+        .deserialize_with_progress::<u128, _>(|_| ())
+        .unwrap();
+    assert_eq!(r, 7);
+}
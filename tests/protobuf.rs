@@ -0,0 +1,49 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::ProtobufLengthDelegate;
+
+type ProtobufSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, ProtobufLengthDelegate, String>;
+type ProtobufDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, ProtobufLengthDelegate, String>;
+
+#[test]
+fn test_string_framing() {
+    // the payload of `message Test2 { string b = 2; }` with b = "testing"
+    // from the protobuf encoding guide, without the field tag 0x12
+    let v = to_vec!(ProtobufSerializer, "testing");
+    assert_eq!(v, [0x07, 0x74, 0x65, 0x73, 0x74, 0x69, 0x6e, 0x67]);
+    assert_eq!(<&str>::deserialize(ProtobufDeserializer::new(v.iter())).unwrap(), "testing");
+
+    assert_eq!(to_vec!(ProtobufSerializer, ""), [0x00]);
+
+    let long = "a".repeat(300);
+    let v = to_vec!(ProtobufSerializer, long.as_str());
+    assert_eq!(&v[..2], &[0xac, 0x02]);
+    assert_eq!(v.len(), 302);
+    assert_eq!(String::deserialize(ProtobufDeserializer::new(v.iter())).unwrap(), long);
+}
+
+#[test]
+fn test_bytes_framing() {
+    let bytes = vec![0xde, 0xad, 0xbe, 0xef];
+    let v = to_vec!(ProtobufSerializer, &bytes);
+    assert_eq!(v, [0x04, 0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(Vec::<u8>::deserialize(ProtobufDeserializer::new(v.iter())).unwrap(), bytes);
+}
+
+#[test]
+fn test_integers_stay_fixed() {
+    let v = to_vec!(ProtobufSerializer, &(150u32, "hi"));
+    assert_eq!(v, [0x96, 0x00, 0x00, 0x00, 0x02, 0x68, 0x69]);
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::net::Ipv4Addr;
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+use tirse::{HumanReadable, Compact};
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Peer {
+    id: u16,
+    name: HumanReadable<Ipv4Addr>,
+    address: Ipv4Addr,
+    inner: HumanReadable<(Ipv4Addr, Compact<Ipv4Addr>)>,
+}
+
+#[test]
+fn test_human_readable_subtree() {
+    let peer = Peer {
+        id: 7,
+        name: HumanReadable(Ipv4Addr::new(10, 0, 0, 1)),
+        address: Ipv4Addr::new(10, 0, 0, 2),
+        inner: HumanReadable((Ipv4Addr::new(1, 2, 3, 4), Compact(Ipv4Addr::new(5, 6, 7, 8)))),
+    };
+    let v = peer.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let text = |s: &str| {
+        let mut v = default_bytes!(s.len()).to_vec();
+        v.extend_from_slice(s.as_bytes());
+        v
+    };
+    let mut expected = default_bytes!(7u16).to_vec();
+    expected.extend(text("10.0.0.1"));
+    expected.extend([10, 0, 0, 2]);
+    expected.extend(text("1.2.3.4"));
+    expected.extend([5, 6, 7, 8]);
+    assert_eq!(v, expected);
+
+    let r = Peer::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, peer);
+}
@@ -0,0 +1,313 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde::Serializer;
+
+use std::slice::Iter;
+use std::collections::BTreeMap;
+
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinarySerializerError;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+type DeserializeFromIter<'a, 'b> = DefaultBinaryDeserializer<'a, &'b mut Iter<'a, u8>, String>;
+
+#[test]
+fn test_length_prefixed_sequences() {
+    let values = vec![1u16, 2, 3];
+
+    let v = values.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..8], default_bytes!(3u64));
+    assert_eq!(v.len(), 8 + 3 * 2);
+
+    // the length tells where the sequence ends
+    let mut v = v;
+    v.push(0xff);
+    let mut iter = v.iter();
+    let r = Vec::<u16>::deserialize(DeserializeFromIter::new(&mut iter)).unwrap();
+    assert_eq!(r, values);
+    assert_eq!(iter.as_slice(), [0xff]);
+
+    // the length of a sequence must be known in advance
+    let s = SerializerIntoVec::new(Vec::new());
+    match s.collect_seq(values.iter().filter(|&&x| x != 2)) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownLength))) => (),
+        Err(e) => panic!("expected unknown length, got {:?}", e),
+        Ok(_) => panic!("expected unknown length"),
+    }
+}
+
+#[test]
+fn test_truncated_sequence() {
+    let values = vec![1u16, 2, 3];
+
+    // the length promises three elements, the input holds two
+    let v = values.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let r = Vec::<u16>::deserialize(DeserializeFromSlice::new(v[..8 + 2 * 2].iter()));
+    // the reader runs out of input rather than the sequence ending early
+    assert!(matches!(r, Err(ErrorAdapter::Inner(Either::Right(_)))));
+}
+
+#[test]
+fn test_map_unknown_length() {
+    use serde::ser::SerializeMap;
+
+    struct Pairs;
+
+    impl Serialize for Pairs {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(None)?;
+            map.serialize_entry(&1u8, &2u8)?;
+            map.serialize_entry(&3u8, &4u8)?;
+            map.end()
+        }
+    }
+
+    // the count of entries must be known in advance, like the length of a sequence
+    match Pairs.serialize(SerializerIntoVec::new(Vec::new())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinarySerializerError::UnknownLength))) => (),
+        Err(e) => panic!("expected unknown length, got {:?}", e),
+        Ok(_) => panic!("expected unknown length"),
+    }
+
+    // unless maps are not prefixed, then the entries run to the end of the input
+    let s = SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false);
+    let v = Pairs.serialize(s).unwrap().consume().into_inner();
+    assert_eq!(v, [1, 2, 3, 4]);
+    let d = DeserializeFromSlice::new(v.iter()).length_prefixed_sequences(false);
+    let r = BTreeMap::<u8, u8>::deserialize(d).unwrap();
+    assert_eq!(r, BTreeMap::from([(1, 2), (3, 4)]));
+}
+
+#[test]
+fn test_unprefixed_maps() {
+    let value = BTreeMap::from([(1u8, 2u16), (3, 4)]);
+
+    let v = (7u8, &value).serialize(SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false)).unwrap().consume().into_inner();
+    let mut expected = vec![7, 1];
+    expected.extend_from_slice(&default_bytes!(2u16));
+    expected.push(3);
+    expected.extend_from_slice(&default_bytes!(4u16));
+    assert_eq!(v, expected);
+
+    let r = <(u8, BTreeMap<u8, u16>)>::deserialize(DeserializeFromSlice::new(v.iter()).length_prefixed_sequences(false)).unwrap();
+    assert_eq!(r, (7, value.clone()));
+
+    // the prefixed form still writes the count of entries
+    let v = value.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..8], default_bytes!(2u64));
+    let r = BTreeMap::<u8, u16>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+}
+
+#[test]
+fn test_empty_value_at_end() {
+    use serde_derive::{Serialize, Deserialize};
+    use tirse::{to_vec, from_slice};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Marker;
+
+    // the value of the last entry takes no bytes, so the input ends before it
+    let value = BTreeMap::from([(1u32, ())]);
+    let v = to_vec(&value).unwrap();
+    assert_eq!(from_slice::<BTreeMap<u32, ()>>(&v).unwrap(), value);
+
+    let value = BTreeMap::from([(1u32, Marker), (2, Marker)]);
+    let v = to_vec(&value).unwrap();
+    assert_eq!(from_slice::<BTreeMap<u32, Marker>>(&v).unwrap(), value);
+
+    // a missing value that takes bytes still fails
+    let v = to_vec(&BTreeMap::from([(1u32, 2u32)])).unwrap();
+    assert!(from_slice::<BTreeMap<u32, u32>>(&v[..v.len() - 4]).is_err());
+}
+
+#[test]
+fn test_unprefixed_sequences() {
+    let values = vec![1u16, 2, 3];
+
+    let s = SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false);
+    let s = 7u8.serialize(s).unwrap();
+    let s = s.collect_seq(values.iter().filter(|&&x| x != 2)).unwrap();
+    let v = s.consume().into_inner();
+    let mut expected = vec![7];
+    expected.extend_from_slice(&default_bytes!(1u16));
+    expected.extend_from_slice(&default_bytes!(3u16));
+    assert_eq!(v, expected);
+
+    // the sequence runs to the end of the input
+    let r = <(u8, Vec<u16>)>::deserialize(DeserializeFromSlice::new(v.iter()).length_prefixed_sequences(false)).unwrap();
+    assert_eq!(r, (7, vec![1, 3]));
+
+    let v = values.serialize(SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false)).unwrap().consume().into_inner();
+    let r = Vec::<u16>::deserialize(DeserializeFromSlice::new(v.iter()).length_prefixed_sequences(false)).unwrap();
+    assert_eq!(r, values);
+}
+
+#[test]
+fn test_size_hint() {
+    use std::fmt;
+    use serde::Deserializer;
+    use serde::de::{Visitor, SeqAccess};
+
+    // the hints seen before each element, and the count of elements
+    struct Hints;
+
+    impl<'de> Visitor<'de> for Hints {
+        type Value = (Vec<Option<usize>>, usize);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a sequence")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut hints = vec![seq.size_hint()];
+            let mut count = 0;
+            while seq.next_element::<u32>()?.is_some() {
+                count += 1;
+                hints.push(seq.size_hint());
+            }
+            Ok((hints, count))
+        }
+    }
+
+    let values = (0..1000).collect::<Vec<u32>>();
+    let v = values.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let (hints, count) = DeserializeFromSlice::new(v.iter()).deserialize_seq(Hints).unwrap();
+    assert_eq!(count, 1000);
+    assert_eq!(hints[0], Some(1000));
+    assert_eq!(hints[1], Some(999));
+    assert_eq!(hints[1000], Some(0));
+
+    // without a length nothing is known
+    let v = values.serialize(SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false)).unwrap().consume().into_inner();
+    let (hints, count) = DeserializeFromSlice::new(v.iter()).length_prefixed_sequences(false).deserialize_seq(Hints).unwrap();
+    assert_eq!(count, 1000);
+    assert!(hints.iter().all(Option::is_none));
+}
+
+#[test]
+fn test_unprefixed_sequence_from_reader() {
+    use std::io::Cursor;
+    use tirse::ReadWrapper;
+
+    type DeserializeFromRead = DefaultBinaryDeserializer<'static, ReadWrapper<Cursor<Vec<u8>>>, String>;
+
+    let values = vec![1u16, 2, 3];
+    let v = (7u8, &values).serialize(SerializerIntoVec::new(Vec::new()).length_prefixed_sequences(false)).unwrap().consume().into_inner();
+
+    // the sequence ends cleanly at the end of the stream
+    let d = DeserializeFromRead::new(ReadWrapper::from(Cursor::new(v.clone()))).length_prefixed_sequences(false);
+    let r = <(u8, Vec<u16>)>::deserialize(d).unwrap();
+    assert_eq!(r, (7, values));
+
+    // a partial element is still an error
+    let d = DeserializeFromRead::new(ReadWrapper::from(Cursor::new(v[..v.len() - 1].to_vec()))).length_prefixed_sequences(false);
+    assert!(<(u8, Vec<u16>)>::deserialize(d).is_err());
+}
+
+#[test]
+fn test_sequence_length_delegate() {
+    use std::collections::BTreeMap;
+    use byteorder::{ByteOrder, LittleEndian};
+    use tirse::{
+        BinarySerializer, BinaryDeserializer, BinarySerializerDelegate, BinaryDeserializerDelegate,
+        DefaultBinaryDeserializerDelegate,
+    };
+
+    // only the count of elements or entries is narrowed to two bytes
+    pub struct ShortCountDelegate;
+
+    impl BinarySerializerDelegate for ShortCountDelegate {
+        type Variant = u32;
+        type Length = usize;
+        type SequenceLength = u16;
+        type Char = u32;
+
+        fn encode_variant(v: u32) -> Self::Variant {
+            v
+        }
+
+        fn encode_length(v: usize) -> Self::Length {
+            v
+        }
+
+        fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+            v as _
+        }
+
+        fn encode_char(v: char) -> Self::Char {
+            v as _
+        }
+    }
+
+    impl BinaryDeserializerDelegate for ShortCountDelegate {
+        type SmallBuffer = [u8; 8];
+
+        fn variant_size() -> usize {
+            DefaultBinaryDeserializerDelegate::variant_size()
+        }
+
+        fn length_size() -> usize {
+            DefaultBinaryDeserializerDelegate::length_size()
+        }
+
+        fn sequence_length_size() -> usize {
+            2
+        }
+
+        fn char_size() -> usize {
+            DefaultBinaryDeserializerDelegate::char_size()
+        }
+
+        fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+        }
+
+        fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+        }
+
+        fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+            Some(usize::from(E::read_u16(bytes)))
+        }
+
+        fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+            DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+        }
+    }
+
+    type ShortSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, ShortCountDelegate, String>;
+    type ShortDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, ShortCountDelegate, String>;
+
+    let values = vec![7u8, 8, 9];
+    let v = values.serialize(ShortSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, [3, 0, 7, 8, 9]);
+    assert_eq!(Vec::<u8>::deserialize(ShortDeserializer::new(v.iter())).unwrap(), values);
+
+    let map = (1u8..3).map(|k| (k, k * 10)).collect::<BTreeMap<u8, u8>>();
+    let v = map.serialize(ShortSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, [2, 0, 1, 10, 2, 20]);
+    assert_eq!(BTreeMap::<u8, u8>::deserialize(ShortDeserializer::new(v.iter())).unwrap(), map);
+
+    // strings keep the length
+    let v = "ab".serialize(ShortSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..8], 2u64.to_le_bytes());
+    assert_eq!(<&str>::deserialize(ShortDeserializer::new(v.iter())).unwrap(), "ab");
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+use speedy::Readable;
+use speedy::Writable;
+use speedy::Endianness;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::SpeedyCompatDelegate;
+
+type SpeedySerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, SpeedyCompatDelegate, String>;
+type SpeedyDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, SpeedyCompatDelegate, String>;
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, Readable, Writable, PartialEq, Debug)]
+pub enum Shape {
+    Empty,
+    Circle(u32),
+    Rect { w: u16, h: u16 },
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, Readable, Writable, PartialEq, Debug)]
+pub struct Record {
+    id: u64,
+    name: String,
+    values: Vec<u16>,
+    flag: bool,
+    letter: char,
+    ratio: f32,
+    shapes: Vec<Shape>,
+}
+
+fn record() -> Record {
+    Record {
+        id: 0x0102030405060708,
+        name: "speedy".to_owned(),
+        values: vec![1, 2, 3],
+        flag: true,
+        letter: 'λ',
+        ratio: 0.5,
+        shapes: vec![Shape::Empty, Shape::Circle(7), Shape::Rect { w: 3, h: 4 }],
+    }
+}
+
+#[test]
+fn test_read_speedy_output() {
+    let v = record().write_to_vec_with_ctx(Endianness::LittleEndian).unwrap();
+
+    let r = Record::deserialize(SpeedyDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, record());
+}
+
+#[test]
+fn test_speedy_reads_output() {
+    let v = record().serialize(SpeedySerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, record().write_to_vec_with_ctx(Endianness::LittleEndian).unwrap());
+
+    let r = Record::read_from_buffer_with_ctx(Endianness::LittleEndian, &v).unwrap();
+    assert_eq!(r, record());
+}
+
+#[test]
+fn test_option_is_not_compatible() {
+    let v = Some(1u8).serialize(SpeedySerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, vec![1, 0, 0, 0, 1]);
+    assert_eq!(Some(1u8).write_to_vec_with_ctx(Endianness::LittleEndian).unwrap(), vec![1, 1]);
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_length_overflow() {
+    use serde::Serializer;
+    use tirse::ErrorAdapter;
+
+    // `speedy` has no room for the length, it is not truncated
+    match SpeedySerializer::new(Vec::new()).serialize_seq(Some(u32::MAX as usize + 1)) {
+        Err(ErrorAdapter::Outer(message)) => assert_eq!(message, "length does not fit in u32"),
+        Err(e) => panic!("unexpected {:?}", e),
+        Ok(_) => panic!("expected the length to overflow"),
+    }
+    assert!(SpeedySerializer::new(Vec::new()).serialize_seq(Some(u32::MAX as usize)).is_ok());
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::TaggedIntDelegate;
+
+type TaggedSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, TaggedIntDelegate, String>;
+type TaggedDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, TaggedIntDelegate, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Old {
+    id: u32,
+    delta: i16,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct New {
+    id: u64,
+    delta: i64,
+    name: String,
+}
+
+#[test]
+fn test_widened_field() {
+    let old = Old { id: 0xdead_beef, delta: -2, name: "old".to_owned() };
+    let v = to_vec!(TaggedSerializer, &old);
+    assert_eq!(&v[..5], &[4, 0xef, 0xbe, 0xad, 0xde]);
+    assert_eq!(&v[5..8], &[2, 0xfe, 0xff]);
+
+    let r = New::deserialize(TaggedDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, New { id: 0xdead_beef, delta: -2, name: "old".to_owned() });
+
+    let r = Old::deserialize(TaggedDeserializer::new(v.iter())).unwrap();
+    assert_eq!(r, old);
+}
+
+#[test]
+fn test_narrowing_is_error() {
+    let v = to_vec!(TaggedSerializer, &1u64);
+    assert!(u32::deserialize(TaggedDeserializer::new(v.iter())).is_err());
+}
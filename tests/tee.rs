@@ -0,0 +1,55 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde_derive::Serialize;
+
+use std::collections::hash_map::DefaultHasher;
+use std::convert::Infallible;
+use std::hash::Hasher;
+
+use tirse::Write;
+use tirse::TeeWrite;
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+
+#[derive(Default)]
+pub struct HashingWrite {
+    hasher: DefaultHasher,
+}
+
+impl Write for HashingWrite {
+    type Error = Infallible;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.hasher.write(bytes);
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct Sample {
+    id: u64,
+    name: String,
+    values: Vec<u16>,
+}
+
+#[test]
+fn test_tee_write() {
+    let sample = Sample { id: 9, name: "tee".to_owned(), values: vec![1, 2, 3] };
+
+    let tee = TeeWrite::new(WriteWrapper::from(Vec::new()), HashingWrite::default());
+    let serializer = DefaultBinarySerializer::<TeeWrite<_, _>, String>::new(tee);
+    let (v, hashing) = sample.serialize(serializer).unwrap().consume().into_inner();
+    let v = v.into_inner();
+
+    let expected = sample
+        .serialize(DefaultBinarySerializer::<WriteWrapper<Vec<u8>>, String>::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    assert_eq!(v, expected);
+
+    let mut hasher = DefaultHasher::new();
+    hasher.write(&v);
+    assert_eq!(hashing.hasher.finish(), hasher.finish());
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::BinarySerializerDelegate;
+use tirse::BinaryDeserializerDelegate;
+use tirse::DefaultBinarySerializerDelegate;
+use tirse::DefaultBinaryDeserializerDelegate;
+
+pub struct MarkedUnitDelegate;
+
+impl BinarySerializerDelegate for MarkedUnitDelegate {
+    type Variant = u32;
+    type Length = usize;
+    type SequenceLength = usize;
+    type Char = u32;
+
+    fn encode_variant(v: u32) -> Self::Variant {
+        DefaultBinarySerializerDelegate::encode_variant(v)
+    }
+
+    fn encode_length(v: usize) -> Self::Length {
+        DefaultBinarySerializerDelegate::encode_length(v)
+    }
+
+    fn encode_sequence_length(v: usize) -> Self::SequenceLength {
+        DefaultBinarySerializerDelegate::encode_sequence_length(v)
+    }
+
+    fn encode_char(v: char) -> Self::Char {
+        DefaultBinarySerializerDelegate::encode_char(v)
+    }
+
+    fn encode_unit() -> &'static [u8] {
+        &[0xc0]
+    }
+}
+
+impl BinaryDeserializerDelegate for MarkedUnitDelegate {
+    type SmallBuffer = [u8; 8];
+
+    fn variant_size() -> usize {
+        DefaultBinaryDeserializerDelegate::variant_size()
+    }
+
+    fn length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::length_size()
+    }
+
+    fn sequence_length_size() -> usize {
+        DefaultBinaryDeserializerDelegate::sequence_length_size()
+    }
+
+    fn char_size() -> usize {
+        DefaultBinaryDeserializerDelegate::char_size()
+    }
+
+    fn decode_variant<E>(bytes: &[u8]) -> u32 where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_variant::<E>(bytes)
+    }
+
+    fn decode_length<E>(bytes: &[u8]) -> usize where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_length::<E>(bytes)
+    }
+
+    fn decode_sequence_length<E>(bytes: &[u8]) -> Option<usize> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_sequence_length::<E>(bytes)
+    }
+
+    fn decode_char<E>(bytes: &[u8]) -> Result<char, u32> where E: ByteOrder {
+        DefaultBinaryDeserializerDelegate::decode_char::<E>(bytes)
+    }
+
+    fn unit_size() -> usize {
+        1
+    }
+
+    fn decode_unit(bytes: &[u8]) -> bool {
+        bytes == [0xc0]
+    }
+}
+
+type Serializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, MarkedUnitDelegate, String>;
+type Deserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, MarkedUnitDelegate, String>;
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct WithUnit {
+    a: u8,
+    b: (),
+    c: u8,
+}
+
+#[test]
+fn test_custom_unit() {
+    let value = WithUnit { a: 1, b: (), c: 2 };
+    let v = value.serialize(Serializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, vec![1, 0xc0, 2]);
+
+    let r = WithUnit::deserialize(Deserializer::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+
+    let v = [1, 0xc1, 2];
+    assert!(WithUnit::deserialize(Deserializer::new(v.iter())).is_err());
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Marker;
+
+#[test]
+fn test_custom_unit_struct() {
+    let value = (1u8, Marker, 2u8);
+    let v = value.serialize(Serializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, vec![1, 0xc0, 2]);
+
+    let r = <(u8, Marker, u8)>::deserialize(Deserializer::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+
+    let v = [1, 0xc1, 2];
+    assert!(<(u8, Marker, u8)>::deserialize(Deserializer::new(v.iter())).is_err());
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use either::Either;
+
+use tirse::WriteWrapper;
+use tirse::ErrorAdapter;
+use tirse::BinaryDeserializerError;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Named {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Target {
+    Named(Named),
+    Point(Point),
+}
+
+fn read_target(d: &mut DeserializeFromSlice) -> Result<Target, String> {
+    d.try_deserialize()
+        .map(Target::Named)
+        .or_else(|_| d.try_deserialize().map(Target::Point))
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_untagged_variants_in_order() {
+    let targets = [
+        Target::Point(Point { x: 0x100, y: 2 }),
+        Target::Named(Named { name: "home".to_owned() }),
+        Target::Point(Point { x: 3, y: 0 }),
+    ];
+
+    let mut s = SerializerIntoVec::new(Vec::new());
+    for target in &targets {
+        s = target.serialize(s).unwrap();
+    }
+    let v = s.consume().into_inner();
+    assert_eq!(v.len(), 8 + (8 + 4) + 8);
+
+    let mut d = DeserializeFromSlice::new(v.iter());
+    for target in &targets {
+        assert_eq!(&read_target(&mut d).unwrap(), target);
+    }
+    assert!(read_target(&mut d).is_err());
+}
+
+#[test]
+fn test_derived_untagged_is_not_supported() {
+    let v = Target::Point(Point { x: 1, y: 2 })
+        .serialize(SerializerIntoVec::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+
+    match Target::deserialize(DeserializeFromSlice::new(v.iter())) {
+        Err(ErrorAdapter::Inner(Either::Left(BinaryDeserializerError::NotSelfDescribing))) => (),
+        r => panic!("expected not self-describing, got {:?}", r),
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Deserialize;
+
+use std::slice::Iter;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::Utf8CharDelegate;
+
+type Utf8Serializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, Utf8CharDelegate, String>;
+type Utf8Deserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, Utf8CharDelegate, String>;
+
+#[test]
+fn test_ascii_is_two_bytes() {
+    assert_eq!(to_vec!(Utf8Serializer, &'a'), vec![1, b'a']);
+    assert_eq!(to_vec!(Utf8Serializer, &'é'), vec![2, 0xc3, 0xa9]);
+    assert_eq!(to_vec!(Utf8Serializer, &'😀'), vec![4, 0xf0, 0x9f, 0x98, 0x80]);
+}
+
+#[test]
+fn test_round_trip() {
+    let value = ('a', '😀', 7u8, 'ж');
+    let v = to_vec!(Utf8Serializer, &value);
+    assert_eq!(v.len(), 2 + 5 + 1 + 3);
+    let r = <(char, char, u8, char)>::deserialize(Utf8Deserializer::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+}
+
+#[test]
+fn test_invalid_char() {
+    for v in [&[0][..], &[5, b'a'], &[2, b'a', b'b'], &[1, 0xff], &[2, 0xc3]] {
+        assert!(char::deserialize(Utf8Deserializer::new(v.iter())).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+#![cfg(feature = "use_std")]
+
+#[macro_use]
+mod common;
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+use std::fmt::Debug;
+
+use byteorder::LittleEndian;
+
+use tirse::WriteWrapper;
+use tirse::BinarySerializer;
+use tirse::BinaryDeserializer;
+use tirse::DefaultBinarySerializer;
+use tirse::VarintIntDelegate;
+
+type VarintSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, VarintIntDelegate, String>;
+type VarintDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, VarintIntDelegate, String>;
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+
+fn round_trip<'a, T>(value: T, buffer: &'a mut Vec<u8>)
+where
+    T: Serialize + Deserialize<'a> + PartialEq + Debug,
+{
+    *buffer = to_vec!(VarintSerializer, &value);
+    let r = T::deserialize(VarintDeserializer::new(buffer.iter())).unwrap();
+    assert_eq!(r, value);
+}
+
+#[test]
+fn test_small_u64_is_one_byte() {
+    assert_eq!(to_vec!(VarintSerializer, &5u64), vec![5]);
+    assert_eq!(to_vec!(VarintSerializer, &127u64), vec![127]);
+    assert_eq!(to_vec!(VarintSerializer, &128u64), vec![0x80, 0x01]);
+    assert_eq!(to_vec!(VarintSerializer, &300u16), vec![0xac, 0x02]);
+}
+
+#[test]
+fn test_round_trip_full_range() {
+    let mut buffer = Vec::new();
+
+    for &v in &[0, 1, 127, 128, 16383, 16384, u64::from(u32::MAX), u64::MAX - 1, u64::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    for &v in &[0, 1, 255, 256, u32::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    for &v in &[0, 1, 255, 256, u16::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    for &v in &[i64::MIN, -1, 0, 1, i64::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    for &v in &[i32::MIN, -1, 0, 1, i32::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    for &v in &[i16::MIN, -1, 0, 1, i16::MAX] {
+        round_trip(v, &mut buffer);
+    }
+    round_trip('z', &mut buffer);
+    round_trip(Some(7u8), &mut buffer);
+}
+
+#[test]
+fn test_out_of_range_varint() {
+    let v = to_vec!(VarintSerializer, &70000u32);
+    assert!(u16::deserialize(VarintDeserializer::new(v.iter())).is_err());
+
+    let v = [0xff; 11];
+    assert!(u64::deserialize(VarintDeserializer::new(v.iter())).is_err());
+}
+
+#[test]
+fn test_space_savings() {
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    pub struct Counters {
+        a: u64,
+        b: u64,
+        c: u64,
+        d: u64,
+        e: u64,
+        f: u64,
+        g: u64,
+        h: u64,
+    }
+
+    let counters = Counters { a: 0, b: 1, c: 2, d: 42, e: 100, f: 127, g: 1000, h: 1 << 40 };
+    let fixed = counters.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let compact = to_vec!(VarintSerializer, &counters);
+
+    assert_eq!(fixed.len(), 64);
+    assert_eq!(compact.len(), 6 + 2 + 6);
+
+    let r = Counters::deserialize(VarintDeserializer::new(compact.iter())).unwrap();
+    assert_eq!(r, counters);
+}
+
+#[test]
+fn test_transcode_fixed_to_varint() {
+    use tirse::{transcode, DefaultEndian, PrimitiveTagDelegate};
+    use tirse::{DefaultBinarySerializerDelegate, DefaultBinaryDeserializerDelegate};
+
+    type Fixed = PrimitiveTagDelegate<DefaultBinarySerializerDelegate>;
+    type Tagged = PrimitiveTagDelegate<DefaultBinaryDeserializerDelegate>;
+    type Compact = PrimitiveTagDelegate<VarintIntDelegate>;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    pub struct Record {
+        id: u64,
+        tags: [u32; 3],
+        name: String,
+        ratio: f32,
+    }
+
+    let record = Record { id: 300, tags: [1, 2, 70000], name: "blob".to_owned(), ratio: 0.5 };
+    let fixed = record
+        .serialize(BinarySerializer::<WriteWrapper<Vec<u8>>, DefaultEndian, Fixed, String>::new(Vec::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    let compact = transcode::<DefaultEndian, Tagged, LittleEndian, Compact>(&fixed).unwrap();
+
+    assert!(compact.len() < fixed.len());
+    let d = BinaryDeserializer::<Iter<u8>, LittleEndian, Compact, String>::new(compact.iter());
+    assert_eq!(Record::deserialize(d).unwrap(), record);
+
+    let untagged = transcode::<DefaultEndian, Tagged, LittleEndian, VarintIntDelegate>(&fixed).unwrap();
+    assert_eq!(Record::deserialize(VarintDeserializer::new(untagged.iter())).unwrap(), record);
+
+    assert!(transcode::<DefaultEndian, DefaultBinaryDeserializerDelegate, LittleEndian, Compact>(&fixed).is_err());
+}
+
+#[test]
+fn test_varint_lengths_and_variants() {
+    use tirse::{VarintSerializerDelegate, VarintDeserializerDelegate};
+
+    type LengthSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, VarintSerializerDelegate, String>;
+    type LengthDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, VarintDeserializerDelegate, String>;
+
+    #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+    pub enum Shape {
+        Point,
+        Line(u16),
+        Polygon(Vec<u16>),
+    }
+
+    let v = (0..300).collect::<Vec<u16>>();
+    let bytes = v.serialize(LengthSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(&bytes[..2], &[0xac, 0x02]);
+    assert_eq!(bytes.len(), 2 + 300 * 2);
+    let r = Vec::<u16>::deserialize(LengthDeserializer::new(bytes.iter())).unwrap();
+    assert_eq!(r, v);
+
+    let value = (Shape::Polygon(vec![1, 2]), Shape::Point, Some(Shape::Line(7)), None::<u32>);
+    let bytes = value.serialize(LengthSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(&bytes[..2], &[2, 2]);
+    let r = <(Shape, Shape, Option<Shape>, Option<u32>)>::deserialize(LengthDeserializer::new(bytes.iter())).unwrap();
+    assert_eq!(r, value);
+}
+
+#[test]
+fn test_zigzag_variants() {
+    use std::fmt;
+    use serde::{Serializer, Deserializer, de::{Visitor, EnumAccess, VariantAccess}};
+    use tirse::ZigzagVarintDelegate;
+
+    type ZigzagSerializer = BinarySerializer<WriteWrapper<Vec<u8>>, LittleEndian, ZigzagVarintDelegate, String>;
+    type ZigzagDeserializer<'a> = BinaryDeserializer<'a, Iter<'a, u8>, LittleEndian, ZigzagVarintDelegate, String>;
+
+    // a unit variant of an enum with 300 variants
+    #[derive(Eq, PartialEq, Debug)]
+    pub struct Wide(u32);
+
+    impl Serialize for Wide {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_unit_variant("Wide", self.0, "V")
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Wide {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct WideVisitor;
+
+            impl<'de> Visitor<'de> for WideVisitor {
+                type Value = Wide;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    write!(formatter, "a variant of Wide")
+                }
+
+                fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+                where
+                    A: EnumAccess<'de>,
+                {
+                    let (index, variant) = data.variant::<u32>()?;
+                    variant.unit_variant().map(|()| Wide(index))
+                }
+            }
+
+            deserializer.deserialize_enum("Wide", &[], WideVisitor)
+        }
+    }
+
+    let fixed = Wide(299).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let compact = Wide(299).serialize(ZigzagSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(fixed.len(), 4);
+    assert_eq!(compact, vec![0xd6, 0x04]);
+
+    for &index in &[0, 1, 63, 64, 299, u32::MAX - 1, u32::MAX] {
+        let bytes = Wide(index).serialize(ZigzagSerializer::new(Vec::new())).unwrap().consume().into_inner();
+        assert!(bytes.len() <= 5);
+        let r = Wide::deserialize(ZigzagDeserializer::new(bytes.iter())).unwrap();
+        assert_eq!(r, Wide(index));
+    }
+    let bytes = Wide(u32::MAX).serialize(ZigzagSerializer::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(bytes, vec![1]);
+
+    for value in [None, Some(Wide(42))] {
+        let bytes = value.serialize(ZigzagSerializer::new(Vec::new())).unwrap().consume().into_inner();
+        let r = Option::<Wide>::deserialize(ZigzagDeserializer::new(bytes.iter())).unwrap();
+        assert_eq!(r, value);
+    }
+}
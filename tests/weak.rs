@@ -0,0 +1,47 @@
+#![cfg(feature = "use_std")]
+
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+use std::rc::{Rc, Weak};
+use std::sync::{self, Arc};
+
+use tirse::to_vec;
+use tirse::DefaultBinaryDeserializer;
+
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Node {
+    id: u32,
+    #[serde(with = "tirse::weak::rc")]
+    parent: Weak<u32>,
+    #[serde(with = "tirse::weak::sync")]
+    shared: sync::Weak<String>,
+}
+
+#[test]
+fn test_live_and_dead_weak() {
+    let parent = Rc::new(7u32);
+    let shared = Arc::new("root".to_owned());
+    let node = Node { id: 1, parent: Rc::downgrade(&parent), shared: Arc::downgrade(&shared) };
+
+    let live = to_vec(&node).unwrap();
+    assert_eq!(live, to_vec(&(1u32, Some(7u32), Some("root"))).unwrap());
+
+    let r = Node::deserialize(DeserializeFromSlice::new(live.iter())).unwrap();
+    assert_eq!(r.id, 1);
+    assert!(r.parent.upgrade().is_none());
+    assert!(r.shared.upgrade().is_none());
+
+    drop(parent);
+    drop(shared);
+    let dead = to_vec(&node).unwrap();
+    assert_eq!(dead, to_vec(&(1u32, None::<u32>, None::<String>)).unwrap());
+
+    let r = Node::deserialize(DeserializeFromSlice::new(dead.iter())).unwrap();
+    assert_eq!(r.id, 1);
+    assert!(r.parent.upgrade().is_none());
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "use_std")]
+
+use serde::Serialize;
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use serde_derive::Serialize;
+use serde_derive::Deserialize;
+
+use std::slice::Iter;
+
+use tirse::WriteWrapper;
+use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
+
+type SerializerIntoVec = DefaultBinarySerializer<WriteWrapper<Vec<u8>>, String>;
+type DeserializeFromSlice<'a> = DefaultBinaryDeserializer<'a, Iter<'a, u8>, String>;
+
+mod big_endian {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    pub fn serialize<S>(v: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        v.to_be_bytes().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <[u8; 8]>::deserialize(deserializer).map(u64::from_be_bytes)
+    }
+}
+
+mod hex {
+    use std::fmt;
+    use serde::{Serializer, Deserializer, de};
+
+    pub fn serialize<S>(v: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = v.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        serializer.serialize_str(&s)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HexVisitor;
+
+        impl<'de> de::Visitor<'de> for HexVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "hex string")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                (0..(v.len() / 2))
+                    .map(|i| u8::from_str_radix(&v[(2 * i)..(2 * i + 2)], 16).map_err(E::custom))
+                    .collect()
+            }
+        }
+
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Record {
+    #[serde(with = "big_endian")]
+    id: u64,
+    #[serde(with = "hex")]
+    digest: Vec<u8>,
+}
+
+#[test]
+fn test_with_module() {
+    let record = Record { id: 0x0102_0304_0506_0708, digest: vec![0xca, 0xfe] };
+    let v = record.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(&v[..8], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(&v[16..], b"cafe");
+
+    let r = Record::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, record);
+}
+
+#[test]
+fn test_with_module_requiring_deserialize_any() {
+    // the format is not self-describing, `deserialize_any` (and `deserialize_identifier`,
+    // `deserialize_ignored_any`) are not supported
+    #[derive(Deserialize, Debug)]
+    #[serde(untagged)]
+    #[allow(dead_code)]
+    pub enum Loose {
+        Number(u64),
+        Text(String),
+    }
+
+    let v = [0; 8];
+    assert!(Loose::deserialize(DeserializeFromSlice::new(v.iter())).is_err());
+    assert!(IgnoredAny::deserialize(DeserializeFromSlice::new(v.iter())).is_err());
+}
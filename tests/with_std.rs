@@ -1,4 +1,8 @@
 #![cfg(feature = "use_std")]
+#![allow(clippy::char_lit_as_u8, clippy::bool_assert_comparison)]
+
+#[macro_use]
+mod common;
 
 use serde::Serialize;
 use serde::Deserialize;
@@ -23,12 +27,9 @@ fn test_read_wrapper() {
     let serializer = SerializerIntoVec::new(v);
     let v = "here".serialize(serializer).unwrap().consume().into_inner();
 
-    assert_eq!(
-        v,
-        vec![
-            4, 0, 0, 0, 0, 0, 0, 0, 'h' as _, 'e' as _, 'r' as _, 'e' as _,
-        ]
-    );
+    let mut expected = default_bytes!(4u64).to_vec();
+    expected.extend_from_slice(&['h' as _, 'e' as _, 'r' as _, 'e' as _]);
+    assert_eq!(v, expected);
 
     let r = <&str>::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v.clone()))));
     assert_eq!(r.is_err(), true);
@@ -43,12 +44,9 @@ fn test_str() {
     let serializer = SerializerIntoVec::new(v);
     let v = "here".serialize(serializer).unwrap().consume().into_inner();
 
-    assert_eq!(
-        v,
-        vec![
-            4, 0, 0, 0, 0, 0, 0, 0, 'h' as _, 'e' as _, 'r' as _, 'e' as _,
-        ]
-    );
+    let mut expected = default_bytes!(4u64).to_vec();
+    expected.extend_from_slice(&['h' as _, 'e' as _, 'r' as _, 'e' as _]);
+    assert_eq!(v, expected);
 
     let s: &str = Deserialize::deserialize(DeserializeFromSlice::new(v.as_slice().iter())).unwrap();
     assert_eq!(s, "here");
@@ -70,9 +68,378 @@ fn test_struct() {
     let serializer = SerializerIntoVec::new(v);
     let v = p.serialize(serializer).unwrap().consume().into_inner();
 
-    assert_eq!(v, vec![17, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(v, [default_bytes!(17u32), default_bytes!(7u32), default_bytes!(0u32)].concat());
     println!("{:?}", v);
 
     let q = Point3d::deserialize(DeserializeFromSlice::new(v.as_slice().iter())).unwrap();
     assert_eq!(p, q);
 }
+
+#[test]
+fn test_128_bit_integers() {
+    let value = (u128::MAX, -0x0102030405060708090a0b0c0d0e0f10i128);
+    let v = value.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..16], default_bytes!(u128::MAX));
+    assert_eq!(v[16..], default_bytes!(value.1));
+
+    let r = <(u128, i128)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, value);
+
+    // the buffered path of a stream
+    let r = <(u128, i128)>::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v.clone())))).unwrap();
+    assert_eq!(r, value);
+
+    // a truncated stream fails cleanly rather than overrunning the buffer
+    let r = u128::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v[..9].to_vec()))));
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_vec_round_trip() {
+    for value in [vec![], vec![1u32], vec![1, 2, 0xdeadbeef]] {
+        // the trailing byte is not consumed, the sequence stops at the written count
+        let v = (&value, 0xffu8).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+        assert_eq!(v[..8], default_bytes!(value.len() as u64));
+        assert_eq!(v.len(), 8 + value.len() * 4 + 1);
+
+        let r = <(Vec<u32>, u8)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+        assert_eq!(r, (value.clone(), 0xff));
+
+        let r = <(Vec<u32>, u8)>::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v)))).unwrap();
+        assert_eq!(r, (value, 0xff));
+    }
+}
+
+#[test]
+fn test_btree_map_round_trip() {
+    use std::collections::BTreeMap;
+
+    let map = (1..=5u32).map(|k| (k, k * 100)).collect::<BTreeMap<_, _>>();
+    let v = (&map, 0xffu8).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v[..8], default_bytes!(5u64));
+    assert_eq!(v.len(), 8 + 5 * 8 + 1);
+
+    let r = <(BTreeMap<u32, u32>, u8)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, (map, 0xff));
+
+    let empty = BTreeMap::<u32, u32>::new();
+    let v = (&empty, 0xffu8).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, [0, 0, 0, 0, 0, 0, 0, 0, 0xff]);
+
+    let r = <(BTreeMap<u32, u32>, u8)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r, (empty, 0xff));
+}
+
+#[test]
+fn test_append_to_borrowed_vec() {
+    type SerializerIntoBorrowed<'a> = DefaultBinarySerializer<&'a mut Vec<u8>, String>;
+
+    let mut buffer = vec![0xaa];
+    "ab".serialize(SerializerIntoBorrowed::new(&mut buffer)).unwrap();
+    7u16.serialize(SerializerIntoBorrowed::new(&mut buffer)).unwrap();
+
+    let mut expected = vec![0xaa];
+    expected.extend_from_slice(&default_bytes!(2u64));
+    expected.extend_from_slice(b"ab");
+    expected.extend_from_slice(&default_bytes!(7u16));
+    assert_eq!(buffer, expected);
+
+    let r = <(String, u16)>::deserialize(DeserializeFromSlice::new(buffer[1..].iter())).unwrap();
+    assert_eq!(r, ("ab".to_owned(), 7));
+}
+
+#[test]
+fn test_read_borrowed_slice() {
+    type DeserializeFromBytes<'a> = DefaultBinaryDeserializer<'a, &'a [u8], String>;
+
+    let v = ("borrowed", 3u8).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let bytes = v.as_slice();
+    let (s, n) = <(&str, u8)>::deserialize(DeserializeFromBytes::new(bytes)).unwrap();
+    assert_eq!((s, n), ("borrowed", 3));
+    assert_eq!(s.as_ptr(), bytes[8..].as_ptr());
+
+    assert!(<(&str, u8)>::deserialize(DeserializeFromBytes::new(&bytes[..bytes.len() - 1])).is_err());
+}
+
+#[test]
+fn test_borrowed_from_buf_read() {
+    use std::io::{BufReader, Read};
+    use tirse::ErrorAdapter;
+
+    type DeserializeFromBufRead<'a> = DefaultBinaryDeserializer<'a, ReadWrapper<BufReader<&'a [u8]>>, String>;
+
+    let v = "line".serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    // the string is read into a temporary, which a `&str` cannot borrow
+    let r = <&str>::deserialize(DeserializeFromBufRead::new(ReadWrapper::from(BufReader::new(v.as_slice()))));
+    match r {
+        Err(ErrorAdapter::Outer(e)) => assert!(e.contains("invalid type: string \"line\"")),
+        r => panic!("unexpected {:?}", r),
+    }
+    let r = String::deserialize(DeserializeFromBufRead::new(ReadWrapper::from(BufReader::new(v.as_slice())))).unwrap();
+    assert_eq!(r, "line");
+
+    // to borrow, the input must outlive the deserializer
+    let mut input = Vec::new();
+    BufReader::new(v.as_slice()).read_to_end(&mut input).unwrap();
+    let r = <&str>::deserialize(DeserializeFromSlice::new(input.iter())).unwrap();
+    assert_eq!(r, "line");
+}
+
+#[test]
+fn test_buf_read_wrapper() {
+    use std::io::{BufRead, BufReader};
+    use tirse::BufReadWrapper;
+
+    type DeserializeFromBufReader<'a> = DefaultBinaryDeserializer<'a, BufReadWrapper<BufReader<&'a [u8]>>, String>;
+    type DeserializeFromCursor<'a> = DefaultBinaryDeserializer<'a, BufReadWrapper<Cursor<&'a [u8]>>, String>;
+
+    let v = (3u8, "line", &b"bytes"[..]).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let d = DeserializeFromBufReader::new(BufReadWrapper::from(BufReader::new(v.as_slice())));
+    let (n, s, b) = <(u8, &str, &[u8])>::deserialize(d).unwrap();
+    assert_eq!((n, s, b), (3, "line", &b"bytes"[..]));
+    assert_eq!(s.as_ptr(), v[9..].as_ptr());
+
+    let d = DeserializeFromCursor::new(BufReadWrapper::from(Cursor::new(v.as_slice())));
+    let r = <(u8, &str, &[u8])>::deserialize(d).unwrap();
+    assert_eq!(r, (3, "line", &b"bytes"[..]));
+
+    // the bytes already in the buffer of the `BufReader` are copied
+    let mut reader = BufReader::new(v.as_slice());
+    reader.fill_buf().unwrap();
+    let d = DeserializeFromBufReader::new(BufReadWrapper::from(reader));
+    assert!(<(u8, &str, &[u8])>::deserialize(d).is_err());
+    let mut reader = BufReader::new(v.as_slice());
+    reader.fill_buf().unwrap();
+    let d = DeserializeFromBufReader::new(BufReadWrapper::from(reader));
+    let r = <(u8, String)>::deserialize(d).unwrap();
+    assert_eq!(r, (3, "line".to_owned()));
+
+    // a truncated input fails
+    let d = DeserializeFromBufReader::new(BufReadWrapper::from(BufReader::new(&v[..v.len() - 1])));
+    assert!(<(u8, &str, &[u8])>::deserialize(d).is_err());
+}
+
+#[test]
+fn test_collect_str() {
+    use std::fmt;
+    use std::net::{IpAddr, Ipv4Addr};
+    use serde::Serializer;
+
+    let address = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+    let v = SerializerIntoVec::new(Vec::new()).collect_str(&address).unwrap().consume().into_inner();
+    let expected = "192.168.0.1".serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    assert_eq!(v, expected);
+
+    let r = String::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r.parse::<IpAddr>().unwrap(), address);
+
+    struct Broken;
+
+    impl fmt::Display for Broken {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let _ = f;
+            Err(fmt::Error)
+        }
+    }
+
+    assert!(SerializerIntoVec::new(Vec::new()).collect_str(&Broken).is_err());
+}
+
+#[test]
+fn test_io_error() {
+    use std::io;
+    use tirse::{ErrorAdapter, BinaryDeserializerError};
+    use either::Either;
+
+    type Error = ErrorAdapter<Either<BinaryDeserializerError, io::Error>, String>;
+
+    let v = 7u32.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let e = u64::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v)))).unwrap_err();
+    assert_eq!(e.io_error().map(io::Error::kind), Some(io::ErrorKind::UnexpectedEof));
+
+    fn read_header(input: &mut impl io::Read) -> Result<u32, Error> {
+        let mut magic = [0; 4];
+        input.read_exact(&mut magic)?;
+        u32::deserialize(DefaultBinaryDeserializer::<_, String>::new(ReadWrapper::from(input)))
+    }
+
+    let mut input = Cursor::new([[0; 4], default_bytes!(5u32)].concat());
+    assert_eq!(read_header(&mut input).unwrap(), 5);
+    let e = read_header(&mut Cursor::new(vec![0, 0])).unwrap_err();
+    assert!(e.io_error().is_some());
+    let e = Error::from_io(io::Error::other("closed"));
+    assert_eq!(e.to_string(), "closed");
+}
+
+#[test]
+fn test_boxed_error_downcast() {
+    use std::error::Error;
+    use tirse::{BinaryDeserializerError, BinarySerializerError};
+
+    let e: Box<dyn Error> = Box::new(BinaryDeserializerError::InvalidBool(2));
+    let e = e.downcast::<BinaryDeserializerError>().unwrap();
+    assert!(matches!(*e, BinaryDeserializerError::InvalidBool(2)));
+
+    let mut v = 2usize.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    v.extend_from_slice(&[0xff, 0xfe]);
+    let e = match String::deserialize(DeserializeFromSlice::new(v.iter())) {
+        Err(tirse::ErrorAdapter::Inner(either::Either::Left(e))) => e,
+        r => panic!("unexpected {:?}", r),
+    };
+    assert!(e.source().unwrap().is::<std::string::FromUtf8Error>());
+
+    let e: Box<dyn Error> = Box::new(BinarySerializerError::DepthLimitExceeded);
+    assert!(e.is::<BinarySerializerError>());
+}
+
+#[test]
+fn test_error_source() {
+    use std::{io, error::Error};
+    use either::Either;
+    use tirse::{ErrorAdapter, BinaryDeserializerError, IoError};
+
+    type Inner = Either<BinaryDeserializerError, io::Error>;
+
+    // the inner error is the source, the `io::Error` of the reader is in it
+    let v = 7u32.serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let e = u64::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v)))).unwrap_err();
+    let source = e.source().and_then(|e| e.downcast_ref::<Inner>()).unwrap();
+    assert_eq!(source.as_ref().right().map(io::Error::kind), Some(io::ErrorKind::UnexpectedEof));
+
+    let e = bool::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(vec![2])))).unwrap_err();
+    let source = e.source().and_then(|e| e.downcast_ref::<Inner>()).unwrap();
+    assert!(matches!(source, Either::Left(BinaryDeserializerError::InvalidBool(2))));
+
+    // any reader, not only an `io::Error`
+    let e = u64::deserialize(DeserializeFromSlice::new([1, 2].iter())).unwrap_err();
+    assert!(e.source().unwrap().is::<Either<BinaryDeserializerError, IoError>>());
+
+    // a message of the `Deserialize` code has no source
+    let e = <ErrorAdapter<Inner, String> as serde::de::Error>::custom("invalid");
+    assert!(e.source().is_none());
+}
+
+#[test]
+fn test_cow_str() {
+    use std::borrow::Cow;
+
+    #[derive(Deserialize, Debug)]
+    pub struct Named<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        id: u16,
+    }
+
+    let v = ("cow", 7u16).serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+
+    let r = Named::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert!(matches!(r.name, Cow::Borrowed("cow")));
+    assert_eq!(r.id, 7);
+
+    let r = Named::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v)))).unwrap();
+    assert!(matches!(r.name, Cow::Owned(ref s) if s == "cow"));
+    assert_eq!(r.id, 7);
+}
+
+#[test]
+fn test_magic_header() {
+    use tirse::{Write, Read};
+
+    let mut s = SerializerIntoVec::new(Vec::new());
+    s.get_mut().write(b"TRSE").unwrap();
+    let v = (1u16, "body").serialize(s).unwrap().consume().into_inner();
+
+    let mut expected = b"TRSE".to_vec();
+    expected.extend_from_slice(&default_bytes!(1u16));
+    expected.extend_from_slice(&default_bytes!(4u64));
+    expected.extend_from_slice(b"body");
+    assert_eq!(v, expected);
+
+    let mut d = DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v)));
+    let mut magic = [0; 4];
+    d.get_mut().read_in_buffer(&mut magic, 4).unwrap();
+    assert_eq!(&magic, b"TRSE");
+    assert_eq!(<(u16, String)>::deserialize(d).unwrap(), (1, "body".to_owned()));
+}
+
+#[test]
+fn test_reset_keeps_capacity() {
+    #[derive(Serialize)]
+    pub struct Tick {
+        seq: u32,
+        name: &'static str,
+    }
+
+    let mut s = SerializerIntoVec::new(Vec::with_capacity(64));
+    let capacity = s.get_ref().get_ref().capacity();
+    let pointer = s.get_ref().get_ref().as_ptr();
+
+    for seq in 0..1000 {
+        s = Tick { seq, name: "tick" }.serialize(s).unwrap();
+        let bytes = s.get_ref().get_ref();
+        assert_eq!(bytes.len(), 4 + 8 + 4);
+        assert_eq!(bytes[..4], default_bytes!(seq));
+        s.reset();
+        assert!(s.get_ref().get_ref().is_empty());
+    }
+    assert_eq!(s.get_ref().get_ref().capacity(), capacity);
+    assert_eq!(s.get_ref().get_ref().as_ptr(), pointer);
+}
+
+#[test]
+fn test_slice_prefix() {
+    use tirse::from_slice_prefix;
+
+    let mut v = default_bytes!(7u32).to_vec();
+    v.extend_from_slice(&default_bytes!(9u32));
+
+    let (first, length) = from_slice_prefix::<u32>(&v).unwrap();
+    assert_eq!((first, length), (7, 4));
+    let rest = &v[length..];
+    let (second, length) = from_slice_prefix::<u32>(rest).unwrap();
+    assert_eq!((second, length), (9, 4));
+    assert!(rest[length..].is_empty());
+    assert!(from_slice_prefix::<u32>(&rest[length..]).is_err());
+}
+
+#[test]
+fn test_str_visitor_from_reader() {
+    use std::fmt;
+    use serde::Deserializer;
+    use serde::de::Visitor;
+
+    // asks for `deserialize_str` and takes a temporary as well as a borrowed string
+    #[derive(Debug, PartialEq)]
+    pub struct Label(String);
+
+    impl<'de> Deserialize<'de> for Label {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct LabelVisitor;
+
+            impl<'de> Visitor<'de> for LabelVisitor {
+                type Value = Label;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    write!(formatter, "a label")
+                }
+
+                fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+                    Ok(Label(v.to_owned()))
+                }
+            }
+
+            deserializer.deserialize_str(LabelVisitor)
+        }
+    }
+
+    let v = ("label", "more").serialize(SerializerIntoVec::new(Vec::new())).unwrap().consume().into_inner();
+    let r = <(Label, String)>::deserialize(DeserializeFromVec::new(ReadWrapper::from(Cursor::new(v.clone())))).unwrap();
+    assert_eq!(r, (Label("label".to_owned()), "more".to_owned()));
+    let r = <(Label, String)>::deserialize(DeserializeFromSlice::new(v.iter())).unwrap();
+    assert_eq!(r.0, Label("label".to_owned()));
+}
@@ -1,11 +1,21 @@
 #![cfg(not(feature = "use_std"))]
 
+#[macro_use]
+mod common;
+
 use serde::Serialize;
+use serde::Deserialize;
 use serde_derive::Serialize;
+use serde_derive::Deserialize;
 
 use tirse::DefaultBinarySerializer;
+use tirse::DefaultBinaryDeserializer;
 use tirse::DisplayCollector;
 use tirse::Write;
+use tirse::RingBuffer;
+use tirse::Cursor;
+use tirse::FixedBuffer;
+use tirse::FixedSerializer;
 
 use core::fmt;
 
@@ -91,9 +101,210 @@ fn test() {
     assert_eq!(
         buffer,
         SmallBuffer {
-            raw: [5, 0, 0, 0, 0, 0, 0, 0],
+            raw: default_bytes!(5u64),
             position: 8,
         }
     );
     println!("{:?}", buffer)
 }
+
+#[test]
+fn test_ring_buffer() {
+    type Serializer = DefaultBinarySerializer<RingBuffer<[u8; 8]>, FakeDisplayCollector>;
+
+    let serializer = Serializer::new(RingBuffer::from([0; 8]));
+    let serializer = Serialize::serialize(&0x11111111u32, serializer).unwrap();
+    let ring = serializer.consume();
+    assert_eq!(ring.len(), 4);
+    assert_eq!(ring.as_slices(), (&[0x11; 4][..], &[][..]));
+
+    let serializer = Serializer::new(ring);
+    let serializer = Serialize::serialize(&0x22222222u32, serializer).unwrap();
+    let serializer = Serialize::serialize(&0x33333333u32, serializer).unwrap();
+    let ring = serializer.consume();
+    assert_eq!(ring.len(), 8);
+    assert_eq!(ring.as_slices(), (&[0x22; 4][..], &[0x33; 4][..]));
+
+    let serializer = Serializer::new(ring);
+    let serializer = Serialize::serialize(&0x4444u16, serializer).unwrap();
+    let ring = serializer.consume();
+    assert_eq!(ring.len(), 8);
+    assert_eq!(ring.as_slices(), (&[0x22, 0x22][..], &[0x33, 0x33, 0x33, 0x33, 0x44, 0x44][..]));
+    assert_eq!(ring.into_inner(), [0x33, 0x33, 0x33, 0x33, 0x44, 0x44, 0x22, 0x22]);
+}
+
+#[test]
+fn test_ring_buffer_value_too_large() {
+    let mut ring = RingBuffer::from([0; 4]);
+    ring.write(&[1, 2]).unwrap();
+    assert!(ring.write(&[0; 5]).is_err());
+    assert_eq!(ring.as_slices(), (&[1, 2][..], &[][..]));
+}
+
+#[test]
+fn test_ring_buffer_wrap_inside_write() {
+    let mut ring = RingBuffer::from([0; 4]);
+    ring.write(&[1, 2, 3]).unwrap();
+    ring.write(&[4, 5]).unwrap();
+    assert_eq!(ring.as_slices(), (&[2, 3, 4][..], &[5][..]));
+}
+
+#[test]
+fn test_cursor() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    pub struct Message<'a> {
+        id: u16,
+        name: &'a str,
+        payload: &'a [u8],
+    }
+
+    let mut buffer = [0; 64];
+    let mut cursor = Cursor::new(&mut buffer[..]);
+    cursor.set_position(2);
+
+    let message = Message { id: 7, name: "tirse", payload: &[1, 2, 3] };
+    let serializer = DefaultBinarySerializer::<Cursor<&mut [u8]>, FakeDisplayCollector>::new(cursor);
+    let cursor = message.serialize(serializer).unwrap().consume();
+    assert_eq!(cursor.position(), 2 + 2 + 8 + 5 + 8 + 3);
+    let end = cursor.position();
+
+    let mut cursor = Cursor::new(&buffer[..end]);
+    cursor.set_position(2);
+    let deserializer = DefaultBinaryDeserializer::<_, FakeDisplayCollector>::new(&mut cursor);
+    let r = Message::deserialize(deserializer).unwrap();
+    assert_eq!(r, message);
+    assert_eq!(cursor.position(), end);
+
+    let name_start = 2 + 2 + 8;
+    assert_eq!(r.name.as_ptr(), buffer[name_start..].as_ptr());
+}
+
+#[test]
+fn test_cursor_overflow() {
+    let mut buffer = [0; 4];
+    let serializer = DefaultBinarySerializer::<Cursor<&mut [u8]>, FakeDisplayCollector>::new(Cursor::new(&mut buffer[..]));
+    assert!(Serialize::serialize(&1u64, serializer).is_err());
+}
+
+#[test]
+fn test_fixed_serializer() {
+    #[derive(Serialize)]
+    pub struct Point3d {
+        x: u32,
+        y: u32,
+        z: u32,
+    }
+
+    let p = Point3d { x: 1, y: 2, z: 3 };
+    let buffer: [u8; 12] = p.serialize(FixedSerializer::<12, FakeDisplayCollector>::new(FixedBuffer::new()))
+        .unwrap()
+        .consume()
+        .into_inner();
+    let mut expected = [0; 12];
+    expected[..4].copy_from_slice(&default_bytes!(1u32));
+    expected[4..8].copy_from_slice(&default_bytes!(2u32));
+    expected[8..].copy_from_slice(&default_bytes!(3u32));
+    assert_eq!(buffer, expected);
+
+    assert!(p.serialize(FixedSerializer::<11, FakeDisplayCollector>::new(FixedBuffer::new())).is_err());
+}
+
+#[test]
+fn test_to_slice_padded() {
+    use tirse::to_slice_padded;
+
+    let mut record = [0xaa; 16];
+    let length = to_slice_padded::<_, FakeDisplayCollector>(&mut record, &(1u32, 2u16)).unwrap();
+    assert_eq!(length, 6);
+    assert_eq!(record[..4], default_bytes!(1u32));
+    assert_eq!(record[4..6], default_bytes!(2u16));
+    assert!(record[6..].iter().all(|&x| x == 0));
+
+    let mut exact = [0xaa; 6];
+    assert_eq!(to_slice_padded::<_, FakeDisplayCollector>(&mut exact, &(1u32, 2u16)).unwrap(), 6);
+
+    let mut small = [0; 4];
+    assert!(to_slice_padded::<_, FakeDisplayCollector>(&mut small, &(1u32, 2u16)).is_err());
+}
+
+#[test]
+fn test_heapless_map() {
+    use heapless::FnvIndexMap;
+    use tirse::to_slice_padded;
+
+    type Map = FnvIndexMap<u8, u16, 4>;
+
+    let mut map = Map::new();
+    for i in 1..=4 {
+        map.insert(i, u16::from(i) * 100).unwrap();
+    }
+
+    let mut buffer = [0; 64];
+    let end = to_slice_padded::<_, FakeDisplayCollector>(&mut buffer, &map).unwrap();
+    assert_eq!(end, 8 + 4 * 3);
+
+    let r = Map::deserialize(DefaultBinaryDeserializer::<_, FakeDisplayCollector>::new(buffer[..end].iter())).unwrap();
+    assert_eq!(r, map);
+
+    let empty = Map::new();
+    let end = to_slice_padded::<_, FakeDisplayCollector>(&mut buffer, &empty).unwrap();
+    let r = Map::deserialize(DefaultBinaryDeserializer::<_, FakeDisplayCollector>::new(buffer[..end].iter())).unwrap();
+    assert!(r.is_empty());
+
+    // a sequence of pairs has the same encoding as a map
+    let entries = [(1u8, 100u16), (2, 200), (3, 300), (4, 400), (5, 500)];
+    let end = to_slice_padded::<_, FakeDisplayCollector>(&mut buffer, &entries[..]).unwrap();
+    assert!(Map::deserialize(DefaultBinaryDeserializer::<_, FakeDisplayCollector>::new(buffer[..end].iter())).is_err());
+}
+
+#[test]
+fn test_discard_collector() {
+    use serde::{Serializer, ser};
+    use tirse::{DiscardCollector, ErrorAdapter};
+
+    pub struct Rejected;
+
+    impl Serialize for Rejected {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let _ = serializer;
+            Err(ser::Error::custom("rejected"))
+        }
+    }
+
+    let serializer = DefaultBinarySerializer::<SmallBuffer, DiscardCollector>::new(SmallBuffer::default());
+    match Rejected.serialize(serializer) {
+        Err(ErrorAdapter::Outer(DiscardCollector)) => (),
+        r => panic!("unexpected {:?}", r.map(|_| ())),
+    }
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn test_heapless_vec() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct Packet {
+        id: u32,
+        payload: [u16; 4],
+        flags: u8,
+    }
+
+    type Serializer = DefaultBinarySerializer<heapless::Vec<u8, 16>, FakeDisplayCollector>;
+
+    let packet = Packet { id: 7, payload: [1, 2, 3, 4], flags: 0x80 };
+    let v = packet.serialize(Serializer::new(heapless::Vec::new())).unwrap().consume();
+    assert_eq!(v.len(), 13);
+
+    let r = Packet::deserialize(DefaultBinaryDeserializer::<_, FakeDisplayCollector>::new(v.iter())).unwrap();
+    assert_eq!(r, packet);
+
+    let large = (packet, 1u32);
+    assert!(large.serialize(Serializer::new(heapless::Vec::new())).is_err());
+
+    let mut v = heapless::Vec::<u8, 4>::new();
+    Write::write(&mut v, &[1, 2, 3]).unwrap();
+    assert!(Write::write(&mut v, &[4, 5]).is_err());
+    assert_eq!(v, [1, 2, 3]);
+}